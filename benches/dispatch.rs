@@ -0,0 +1,86 @@
+//! One monster turn through the turn systems, with every system waiting for the one before
+//! and with the dispatcher's graph, where map indexing runs beside melee combat, and item
+//! removal, hunger and particle spawning run beside each other. Both run on the default
+//! pool, so visibility works out the monsters' fields of view with `par_join` in either.
+//! Every viewshed is made stale before each turn so that work is done every time. With a
+//! single CPU the two come out the same.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use hello_rltk::headless::HeadlessRunner;
+use hello_rltk::*;
+use specs::prelude::*;
+
+/// A generated level packed with `monsters` goblins, all of them about to take a turn.
+fn crowded_level(monsters: usize) -> HeadlessRunner {
+    let mut runner = HeadlessRunner::new(1);
+    let player = runner.player();
+    let ecs = &mut runner.state.ecs;
+    ecs.write_storage::<CombatStats>()
+        .get_mut(player)
+        .unwrap()
+        .hp = i32::MAX / 2;
+
+    let floor: Vec<(i32, i32)> = {
+        let map = ecs.fetch::<Map>();
+        map.tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| **tile == TileType::Floor)
+            .map(|(idx, _)| (idx as i32 % map.width, idx as i32 / map.width))
+            .collect()
+    };
+    for i in 0..monsters {
+        let (x, y) = floor[i * 7919 % floor.len()];
+        raws::spawn_named(ecs, "Goblin", x, y).unwrap();
+    }
+    *ecs.write_resource::<RunState>() = RunState::MonsterTurn;
+    runner
+}
+
+/// One monster turn with every viewshed stale, so every field of view is recomputed.
+fn monster_turn(ecs: &mut World, dispatch: &mut dyn FnMut(&World)) {
+    {
+        let entities = ecs.entities();
+        let mut my_turn = ecs.write_storage::<MyTurn>();
+        for (entity, viewshed, _monster) in (
+            &entities,
+            &mut ecs.write_storage::<Viewshed>(),
+            &ecs.read_storage::<Monster>(),
+        )
+            .join()
+        {
+            viewshed.dirty = true;
+            my_turn.insert(entity, MyTurn {}).unwrap();
+        }
+    }
+    dispatch(ecs);
+    ecs.maintain();
+
+    let particles: Vec<Entity> = (&ecs.entities(), &ecs.read_storage::<ParticleLifetime>())
+        .join()
+        .map(|(entity, _)| entity)
+        .collect();
+    ecs.delete_entities(&particles).unwrap();
+    ecs.maintain();
+}
+
+fn dispatch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("monster_turn");
+    for &monsters in &[100, 1000, 4000] {
+        let mut runner = crowded_level(monsters);
+        let mut systems = chained_turn_systems().build();
+        group.bench_function(BenchmarkId::new("chained", monsters), |b| {
+            b.iter(|| monster_turn(&mut runner.state.ecs, &mut |ecs| systems.dispatch(ecs)))
+        });
+
+        let mut runner = crowded_level(monsters);
+        let mut systems = turn_systems().build();
+        group.bench_function(BenchmarkId::new("parallel", monsters), |b| {
+            b.iter(|| monster_turn(&mut runner.state.ecs, &mut |ecs| systems.dispatch(ecs)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, dispatch);
+criterion_main!(benches);
@@ -0,0 +1,124 @@
+use super::{Attributes, Skills};
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Attribute {
+    Might,
+    Agility,
+    Toughness,
+    Intellect,
+}
+
+impl Attribute {
+    pub const ALL: [Attribute; 4] = [
+        Attribute::Might,
+        Attribute::Agility,
+        Attribute::Toughness,
+        Attribute::Intellect,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Attribute::Might => "Might",
+            Attribute::Agility => "Agility",
+            Attribute::Toughness => "Toughness",
+            Attribute::Intellect => "Intellect",
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Skill {
+    Melee,
+    Defense,
+    Magic,
+    Stealth,
+}
+
+impl Skill {
+    pub const ALL: [Skill; 4] = [Skill::Melee, Skill::Defense, Skill::Magic, Skill::Stealth];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Skill::Melee => "Melee",
+            Skill::Defense => "Defense",
+            Skill::Magic => "Magic",
+            Skill::Stealth => "Stealth",
+        }
+    }
+}
+
+impl Attributes {
+    pub fn get(&self, attribute: Attribute) -> i32 {
+        match attribute {
+            Attribute::Might => self.might,
+            Attribute::Agility => self.agility,
+            Attribute::Toughness => self.toughness,
+            Attribute::Intellect => self.intellect,
+        }
+    }
+
+    pub fn get_mut(&mut self, attribute: Attribute) -> &mut i32 {
+        match attribute {
+            Attribute::Might => &mut self.might,
+            Attribute::Agility => &mut self.agility,
+            Attribute::Toughness => &mut self.toughness,
+            Attribute::Intellect => &mut self.intellect,
+        }
+    }
+}
+
+impl Skills {
+    pub fn get(&self, skill: Skill) -> i32 {
+        match skill {
+            Skill::Melee => self.melee,
+            Skill::Defense => self.defense,
+            Skill::Magic => self.magic,
+            Skill::Stealth => self.stealth,
+        }
+    }
+
+    pub fn get_mut(&mut self, skill: Skill) -> &mut i32 {
+        match skill {
+            Skill::Melee => &mut self.melee,
+            Skill::Defense => &mut self.defense,
+            Skill::Magic => &mut self.magic,
+            Skill::Stealth => &mut self.stealth,
+        }
+    }
+}
+
+/// The modifier an attribute gives: nothing at 10, and one more or less for every two
+/// points above or below that.
+pub fn attribute_bonus(value: i32) -> i32 {
+    (value - 10).div_euclid(2)
+}
+
+/// Hit points gained with each character level.
+pub fn hp_per_level(attributes: &Attributes) -> i32 {
+    (5 + attribute_bonus(attributes.toughness)).max(1)
+}
+
+/// Maximum hit points at character `level`. Monsters don't level, and count as level 1.
+pub fn max_hp(attributes: &Attributes, level: i32) -> i32 {
+    (2 * attributes.toughness + level * hp_per_level(attributes)).max(1)
+}
+
+/// How well a creature lands its blows in melee.
+pub fn to_hit(attributes: &Attributes, skills: &Skills) -> i32 {
+    skills.melee + attribute_bonus(attributes.agility)
+}
+
+/// Damage added to every melee hit, on top of the weapon's dice.
+pub fn melee_damage(attributes: &Attributes) -> i32 {
+    attribute_bonus(attributes.might)
+}
+
+/// How much harder a creature is to hit than a sitting target.
+pub fn dodge(attributes: &Attributes, skills: &Skills) -> i32 {
+    skills.defense + attribute_bonus(attributes.agility)
+}
+
+/// Extra damage dealt by the damaging items a creature uses.
+pub fn spell_damage(attributes: &Attributes, skills: &Skills) -> i32 {
+    skills.magic + attribute_bonus(attributes.intellect)
+}
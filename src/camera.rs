@@ -0,0 +1,98 @@
+use super::{tile_glyph, Hidden, Map, Position, Renderable};
+use rltk::{Point, Rltk};
+use specs::prelude::*;
+
+/// Size of the map area on screen; the rows below it belong to the UI panel.
+pub const VIEW_WIDTH: i32 = 80;
+pub const VIEW_HEIGHT: i32 = 43;
+
+/// The part of the map that is on screen. It follows the player, but stops at the map's
+/// edges so no screen space is wasted beyond them.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Camera {
+    pub min_x: i32,
+    pub min_y: i32,
+}
+
+impl Camera {
+    pub fn centered_on(center: Point, map: &Map) -> Camera {
+        let clamp_axis = |center: i32, view: i32, size: i32| {
+            (center - view / 2).clamp(0, i32::max(0, size - view))
+        };
+        Camera {
+            min_x: clamp_axis(center.x, VIEW_WIDTH, map.width),
+            min_y: clamp_axis(center.y, VIEW_HEIGHT, map.height),
+        }
+    }
+
+    /// The camera for the current frame, centred on the player.
+    pub fn for_player(ecs: &World) -> Camera {
+        Camera::centered_on(*ecs.fetch::<Point>(), &ecs.fetch::<Map>())
+    }
+
+    /// Where a map position is drawn, or `None` if it is off screen.
+    pub fn world_to_screen(&self, world: Point) -> Option<Point> {
+        let screen = Point::new(world.x - self.min_x, world.y - self.min_y);
+        if on_screen(screen) {
+            Some(screen)
+        } else {
+            None
+        }
+    }
+
+    /// The map position under a screen cell, or `None` if the cell isn't over the map.
+    pub fn screen_to_world(&self, screen: Point, map: &Map) -> Option<Point> {
+        if !on_screen(screen) {
+            return None;
+        }
+        let x = screen.x + self.min_x;
+        let y = screen.y + self.min_y;
+        if x >= map.width || y >= map.height {
+            None
+        } else {
+            Some(Point::new(x, y))
+        }
+    }
+}
+
+fn on_screen(screen: Point) -> bool {
+    (0..VIEW_WIDTH).contains(&screen.x) && (0..VIEW_HEIGHT).contains(&screen.y)
+}
+
+/// Draws the visible part of the map and everything on it that the player can see.
+pub fn render_camera(ecs: &World, ctx: &mut Rltk) {
+    let map = ecs.fetch::<Map>();
+    let camera = Camera::for_player(ecs);
+
+    for screen_y in 0..VIEW_HEIGHT {
+        for screen_x in 0..VIEW_WIDTH {
+            let world = match camera.screen_to_world(Point::new(screen_x, screen_y), &map) {
+                None => continue,
+                Some(world) => world,
+            };
+            let idx = map.xy_idx(world.x, world.y);
+            if map.revealed_tiles[idx] {
+                let (glyph, fg, bg) = tile_glyph(idx, &map);
+                ctx.set(screen_x, screen_y, fg, bg, glyph);
+            }
+        }
+    }
+
+    let positions = ecs.read_storage::<Position>();
+    let renderables = ecs.read_storage::<Renderable>();
+    let hidden = ecs.read_storage::<Hidden>();
+
+    let mut data = (&positions, &renderables, !&hidden)
+        .join()
+        .collect::<Vec<_>>();
+    data.sort_by_key(|&a| std::cmp::Reverse(a.1.render_order));
+    for (pos, render, _hidden) in data.iter() {
+        let idx = map.xy_idx(pos.x, pos.y);
+        if !map.visible_tiles[idx] {
+            continue;
+        }
+        if let Some(screen) = camera.world_to_screen(Point::new(pos.x, pos.y)) {
+            ctx.set(screen.x, screen.y, render.fg, render.bg, render.glyph)
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use super::DamageType;
+use rltk::{DiceType, RandomNumberGenerator};
+
+/// What a creature without a weapon hits for.
+pub const UNARMED_DAMAGE: DiceType = DiceType {
+    n_dice: 1,
+    die_type: 4,
+    bonus: 0,
+};
+
+pub const UNARMED_DAMAGE_TYPE: DamageType = DamageType::Blunt;
+
+/// Armour class of a creature that neither dodges nor wears armour.
+pub const BASE_ARMOR_CLASS: i32 = 10;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum AttackRoll {
+    Fumble,
+    Miss,
+    Hit,
+    Critical,
+}
+
+impl AttackRoll {
+    pub fn hits(self) -> bool {
+        matches!(self, AttackRoll::Hit | AttackRoll::Critical)
+    }
+}
+
+/// What an attack comes to when the d20 shows `natural`. A natural 20 always hits, and
+/// critically; a natural 1 always misses. Anything else hits if `natural + attack_bonus`
+/// reaches the target's armour class.
+pub fn resolve_attack(natural: i32, attack_bonus: i32, armor_class: i32) -> AttackRoll {
+    match natural {
+        20 => AttackRoll::Critical,
+        1 => AttackRoll::Fumble,
+        _ if natural + attack_bonus >= armor_class => AttackRoll::Hit,
+        _ => AttackRoll::Miss,
+    }
+}
+
+/// How hard a creature is to hit: its dodge, plus whatever armour it has on.
+pub fn armor_class(dodge: i32, armor: i32) -> i32 {
+    BASE_ARMOR_CLASS + dodge + armor
+}
+
+/// Rolls a hit's damage. A critical hit rolls the dice twice, but adds their bonus once.
+pub fn roll_damage(rng: &mut RandomNumberGenerator, dice: DiceType, critical: bool) -> i32 {
+    let mut damage = rng.roll(dice);
+    if critical {
+        damage += rng.roll_dice(dice.n_dice, dice.die_type);
+    }
+    damage
+}
+
+/// Dice in the usual notation: `1d6`, `2d4+1`, `1d8-1`.
+pub fn dice_string(dice: DiceType) -> String {
+    match dice.bonus {
+        0 => format!("{}d{}", dice.n_dice, dice.die_type),
+        bonus => format!("{}d{}{:+}", dice.n_dice, dice.die_type, bonus),
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum DamageResponse {
+    Normal,
+    Resisted,
+    Immune,
+    Vulnerable,
+}
+
+/// How much of a hit gets through to something that is `resistant`, `immune` or
+/// `vulnerable` to its type. Immunity stops it outright, resistance halves it, rounding
+/// down, and vulnerability doubles it. Resistance and vulnerability together cancel out.
+pub fn mitigate(
+    amount: i32,
+    resistant: bool,
+    immune: bool,
+    vulnerable: bool,
+) -> (i32, DamageResponse) {
+    match (immune, resistant, vulnerable) {
+        (true, _, _) => (0, DamageResponse::Immune),
+        (false, true, false) => (amount / 2, DamageResponse::Resisted),
+        (false, false, true) => (amount * 2, DamageResponse::Vulnerable),
+        _ => (amount, DamageResponse::Normal),
+    }
+}
@@ -2,15 +2,103 @@ use rltk::RGB;
 use serde::{Deserialize, Serialize};
 use specs::error::NoError;
 use specs::prelude::*;
-use specs::saveload::{ConvertSaveload, Marker};
+use specs::saveload::{ConvertSaveload, Marker, SimpleMarker};
 use specs_derive::*;
 
+/// Expands to `$callback!(args.., Position, OtherLevelPosition, ..)` with every component
+/// in the game. Registering, saving and loading all expand this one list, so a new
+/// component only has to be added here.
+macro_rules! with_all_components {
+    ($callback:ident $(, $arg:expr)*) => {
+        $callback!(
+            $($arg,)*
+            Position,
+            OtherLevelPosition,
+            Renderable,
+            ParticleLifetime,
+            Player,
+            Viewshed,
+            Monster,
+            Name,
+            BlocksTile,
+            CombatStats,
+            SufferDamage,
+            WantsToMelee,
+            Item,
+            Consumable,
+            Ranged,
+            InflictsDamage,
+            AreaOfEffect,
+            InflictsStatus,
+            ProvidesHealing,
+            ProvidesFood,
+            RevealsMap,
+            MeleeWeapon,
+            DefenseBonus,
+            InBackpack,
+            Equippable,
+            Equipped,
+            WantsToPickupItem,
+            WantsToUseItem,
+            WantsToDropItem,
+            WantsToRemoveItem,
+            HungerClock,
+            Hidden,
+            EntryTrigger,
+            EntityMoved,
+            SingleActivation,
+            Energy,
+            MyTurn,
+            Experience,
+            ExperienceValue,
+            Attributes,
+            Skills,
+            Resistant,
+            Immune,
+            Vulnerable,
+            StatusEffects,
+            SerializationHelper
+        )
+    };
+}
+
+macro_rules! register_individually {
+    ($ecs:expr, $( $type:ty),*) => {
+        $(
+        $ecs.register::<$type>();
+        )*
+    };
+}
+
+macro_rules! component_names {
+    ($( $type:ty),*) => {
+        &[$(stringify!($type)),*]
+    };
+}
+
+/// Every component's name, which is also the key of its storage in a save.
+pub const COMPONENT_NAMES: &[&str] = with_all_components!(component_names);
+
+pub fn register_components(ecs: &mut World) {
+    with_all_components!(register_individually, ecs);
+    ecs.register::<SimpleMarker<SerializeMe>>();
+}
+
 #[derive(Component, ConvertSaveload, Clone)]
 pub struct Position {
     pub x: i32,
     pub y: i32,
 }
 
+/// Where an entity was left on a level the player isn't on. It takes the place of
+/// `Position` until the player returns.
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct OtherLevelPosition {
+    pub x: i32,
+    pub y: i32,
+    pub depth: i32,
+}
+
 #[derive(Component, ConvertSaveload, Clone)]
 pub struct Renderable {
     pub glyph: rltk::FontCharType,
@@ -49,8 +137,6 @@ pub struct BlocksTile {}
 pub struct CombatStats {
     pub max_hp: i32,
     pub hp: i32,
-    pub defense: i32,
-    pub power: i32,
 }
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
@@ -58,24 +144,120 @@ pub struct WantsToMelee {
     pub target: Entity,
 }
 
-#[derive(Component, Debug, ConvertSaveload, Clone)]
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum DamageType {
+    Slashing,
+    Piercing,
+    Blunt,
+    Fire,
+    Cold,
+    Poison,
+}
+
+impl DamageType {
+    pub fn name(self) -> &'static str {
+        match self {
+            DamageType::Slashing => "slashing",
+            DamageType::Piercing => "piercing",
+            DamageType::Blunt => "blunt",
+            DamageType::Fire => "fire",
+            DamageType::Cold => "cold",
+            DamageType::Poison => "poison",
+        }
+    }
+}
+
+/// One hit waiting to be applied. `source` is whoever dealt it, if anyone did. Hits
+/// without a type, like starvation, can't be resisted.
+#[derive(Debug, Clone)]
+pub struct Damage {
+    pub amount: i32,
+    pub source: Option<Entity>,
+    pub damage_type: Option<DamageType>,
+}
+
+#[derive(Component, Debug, Clone)]
 pub struct SufferDamage {
-    pub amount: Vec<i32>,
+    pub amount: Vec<Damage>,
 }
 
 impl SufferDamage {
-    pub fn new_damage(store: &mut WriteStorage<SufferDamage>, victim: Entity, amount: i32) {
+    pub fn new_damage(
+        store: &mut WriteStorage<SufferDamage>,
+        victim: Entity,
+        amount: i32,
+        source: Option<Entity>,
+        damage_type: Option<DamageType>,
+    ) {
+        let damage = Damage {
+            amount,
+            source,
+            damage_type,
+        };
         if let Some(suffering) = store.get_mut(victim) {
-            suffering.amount.push(amount);
+            suffering.amount.push(damage);
         } else {
             let dmg = SufferDamage {
-                amount: vec![amount],
+                amount: vec![damage],
             };
             store.insert(victim, dmg).expect("Unable to insert damage");
         }
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DamageData<M> {
+    pub amount: i32,
+    pub source: Option<M>,
+    pub damage_type: Option<DamageType>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SufferDamageData<M> {
+    pub amount: Vec<DamageData<M>>,
+}
+
+// The derive can't see through `Vec<Damage>` to the entities inside
+impl<M: Marker + Serialize> ConvertSaveload<M> for SufferDamage
+where
+    for<'de> M: Deserialize<'de>,
+{
+    type Data = SufferDamageData<M>;
+    type Error = NoError;
+
+    fn convert_into<F>(&self, mut ids: F) -> Result<Self::Data, Self::Error>
+    where
+        F: FnMut(Entity) -> Option<M>,
+    {
+        let amount = self
+            .amount
+            .iter()
+            .map(|damage| DamageData {
+                amount: damage.amount,
+                source: damage.source.and_then(&mut ids),
+                damage_type: damage.damage_type,
+            })
+            .collect();
+        Ok(SufferDamageData { amount })
+    }
+
+    fn convert_from<F>(data: Self::Data, mut ids: F) -> Result<Self, Self::Error>
+    where
+        F: FnMut(M) -> Option<Entity>,
+    {
+        let amount = data
+            .amount
+            .into_iter()
+            .map(|damage| Damage {
+                amount: damage.amount,
+                source: damage.source.and_then(&mut ids),
+                damage_type: damage.damage_type,
+            })
+            .collect();
+        Ok(SufferDamage { amount })
+    }
+}
+
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Item {}
 
@@ -90,6 +272,7 @@ pub struct Ranged {
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct InflictsDamage {
     pub damage: i32,
+    pub damage_type: DamageType,
 }
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
@@ -97,9 +280,10 @@ pub struct AreaOfEffect {
     pub radius: i32,
 }
 
-#[derive(Component, Debug, ConvertSaveload, Clone)]
-pub struct Confusion {
-    pub turns: i32,
+/// Puts `effect` on whatever the item is used on.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct InflictsStatus {
+    pub effect: StatusEffect,
 }
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
@@ -113,9 +297,13 @@ pub struct ProvidesFood {}
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct RevealsMap {}
 
-#[derive(Component, ConvertSaveload, Clone)]
-pub struct MeleePowerBonus {
-    pub power: i32,
+/// A weapon's damage profile: the dice it rolls on a hit, the kind of damage it does, and
+/// what it adds to the attack roll.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct MeleeWeapon {
+    pub damage: rltk::DiceType,
+    pub damage_type: DamageType,
+    pub hit_bonus: i32,
 }
 
 #[derive(Component, ConvertSaveload, Clone)]
@@ -193,9 +381,113 @@ pub struct EntityMoved {}
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct SingleActivation {}
 
+/// Place in the turn schedule. Every scheduler tick adds `speed` to `energy`, and the
+/// entity acts whenever it has enough energy to pay for an action.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Energy {
+    pub speed: i32,
+    pub energy: i32,
+}
+
+/// The player's character level, and experience towards the next one.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Experience {
+    pub level: i32,
+    pub xp: i32,
+}
+
+/// Experience awarded to whoever kills this.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct ExperienceValue {
+    pub xp: i32,
+}
+
+/// What a creature is made of. 10 is average; see `attributes` for what each one derives.
+#[derive(Component, Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Attributes {
+    pub might: i32,
+    pub agility: i32,
+    pub toughness: i32,
+    pub intellect: i32,
+}
+
+impl Default for Attributes {
+    fn default() -> Attributes {
+        Attributes {
+            might: 10,
+            agility: 10,
+            toughness: 10,
+            intellect: 10,
+        }
+    }
+}
+
+/// What a creature has trained at, on top of its attributes. Untrained is 0.
+#[derive(Component, Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Skills {
+    pub melee: i32,
+    pub defense: i32,
+    pub magic: i32,
+    pub stealth: i32,
+}
+
+/// Damage types that only do half damage to this.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Resistant {
+    pub damage_types: Vec<DamageType>,
+}
+
+/// Damage types that do no damage at all to this.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Immune {
+    pub damage_types: Vec<DamageType>,
+}
+
+/// Damage types that do double damage to this.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Vulnerable {
+    pub damage_types: Vec<DamageType>,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum StatusKind {
+    Confusion,
+    Poison,
+    Burning,
+    Regeneration,
+    Slow,
+    Haste,
+    Paralysis,
+    Blindness,
+}
+
+/// One status effect: what it is, how many more of its bearer's turns it lasts, and how
+/// hard it hits for the kinds that deal damage or heal each turn.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub turns: i32,
+    #[serde(default)]
+    pub potency: i32,
+}
+
+/// Everything currently affecting a creature, at most one effect of each kind. See
+/// `status_effects` for how they stack and what they do.
+#[derive(Component, Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StatusEffects {
+    pub effects: Vec<StatusEffect>,
+}
+
+/// Marks entities whose turn it is.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct MyTurn {}
+
 pub struct SerializeMe;
 
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct SerializationHelper {
     pub map: super::map::Map,
+    pub dungeon: super::dungeon::MasterDungeonMap,
+    pub clock: super::game_clock::GameClock,
+    pub seed: u64,
 }
@@ -1,22 +1,122 @@
-use super::{gamelog::GameLog, CombatStats, Map, Name, Player, Position, RunState, SufferDamage};
+use super::{
+    combat::{self, DamageResponse},
+    gamelog::{GameLog, LogCategory, LogEntry},
+    saveload_system, CombatStats, Experience, ExperienceValue, Immune, Map, Name, Player, Position,
+    Resistant, RunState, SufferDamage, Vulnerable,
+};
 use specs::prelude::*;
 
 pub struct DamageSystem {}
 
 impl<'a> System<'a> for DamageSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         WriteStorage<'a, CombatStats>,
         WriteStorage<'a, SufferDamage>,
         ReadStorage<'a, Position>,
         WriteExpect<'a, Map>,
         Entities<'a>,
+        ReadStorage<'a, ExperienceValue>,
+        WriteStorage<'a, Experience>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Resistant>,
+        ReadStorage<'a, Immune>,
+        ReadStorage<'a, Vulnerable>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut stats, mut damage, positions, mut map, entities) = data;
+        let (
+            mut stats,
+            mut damage,
+            positions,
+            mut map,
+            entities,
+            experience_values,
+            mut experience,
+            player_entity,
+            mut log,
+            names,
+            resistant,
+            immune,
+            vulnerable,
+        ) = data;
 
-        for (entity, mut stats, damage) in (&entities, &mut stats, &damage).join() {
-            stats.hp -= damage.amount.iter().sum::<i32>();
+        for (entity, stats, damage) in (&entities, &mut stats, &damage).join() {
+            for hit in damage.amount.iter() {
+                let (amount, response) = match hit.damage_type {
+                    None => (hit.amount, DamageResponse::Normal),
+                    Some(damage_type) => combat::mitigate(
+                        hit.amount,
+                        resistant
+                            .get(entity)
+                            .is_some_and(|r| r.damage_types.contains(&damage_type)),
+                        immune
+                            .get(entity)
+                            .is_some_and(|i| i.damage_types.contains(&damage_type)),
+                        vulnerable
+                            .get(entity)
+                            .is_some_and(|v| v.damage_types.contains(&damage_type)),
+                    ),
+                };
+                if let (Some(name), Some(damage_type)) = (names.get(entity), hit.damage_type) {
+                    let entry = LogEntry::new(LogCategory::Combat).name(&name.name);
+                    let entry = match response {
+                        DamageResponse::Normal => None,
+                        DamageResponse::Immune => Some(
+                            entry
+                                .text(" resisted all of the ")
+                                .text(damage_type.name())
+                                .text(" damage"),
+                        ),
+                        DamageResponse::Resisted => Some(
+                            entry
+                                .text(" resisted the ")
+                                .text(damage_type.name())
+                                .text(" damage, taking ")
+                                .damage(amount)
+                                .text(" hp"),
+                        ),
+                        DamageResponse::Vulnerable => Some(
+                            entry
+                                .text(" is vulnerable to ")
+                                .text(damage_type.name())
+                                .text(", taking ")
+                                .damage(amount)
+                                .text(" hp!"),
+                        ),
+                    };
+                    if let Some(entry) = entry {
+                        log.push(entry);
+                    }
+                }
+
+                let was_alive = stats.hp > 0;
+                stats.hp -= amount;
+                if !was_alive || stats.hp > 0 {
+                    continue;
+                }
+
+                // The killing blow earns the experience
+                let xp = experience_values.get(entity).map_or(0, |value| value.xp);
+                let killer = hit
+                    .source
+                    .and_then(|source| experience.get_mut(source).map(|exp| (source, exp)));
+                if let Some((killer, exp)) = killer {
+                    if xp > 0 {
+                        exp.xp += xp;
+                        if killer == *player_entity {
+                            log.push(
+                                LogEntry::new(LogCategory::Combat)
+                                    .text("You gain ")
+                                    .colored(xp, rltk::RGB::named(rltk::CYAN))
+                                    .text(" experience."),
+                            );
+                        }
+                    }
+                }
+            }
             let pos = positions.get(entity);
             if let Some(pos) = pos {
                 let idx = map.xy_idx(pos.x, pos.y);
@@ -31,6 +131,7 @@ impl<'a> System<'a> for DamageSystem {
 
 pub fn delete_the_dead(ecs: &mut World) {
     let mut dead: Vec<Entity> = Vec::new();
+    let mut player_died = false;
     {
         let combat_stats = ecs.read_storage::<CombatStats>();
         let players = ecs.read_storage::<Player>();
@@ -44,13 +145,18 @@ pub fn delete_the_dead(ecs: &mut World) {
                     None => {
                         let victim_name = names.get(entity);
                         if let Some(victim_name) = victim_name {
-                            log.entries.push(format!("{} is dead", &victim_name.name));
+                            log.push(
+                                LogEntry::new(LogCategory::Combat)
+                                    .name(&victim_name.name)
+                                    .text(" is dead"),
+                            );
                         }
                         dead.push(entity)
                     }
                     Some(_) => {
                         let mut runstate = ecs.write_resource::<RunState>();
                         *runstate = RunState::GameOver;
+                        player_died = true;
                     }
                 }
             }
@@ -60,4 +166,14 @@ pub fn delete_the_dead(ecs: &mut World) {
     for victim in dead {
         ecs.delete_entity(victim).expect("Unable to delete")
     }
+
+    if player_died {
+        if let Err(err) = saveload_system::delete_save(ecs) {
+            let mut log = ecs.write_resource::<GameLog>();
+            log.say(
+                LogCategory::System,
+                format!("Couldn't delete the save: {}", err),
+            );
+        }
+    }
 }
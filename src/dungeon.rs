@@ -0,0 +1,89 @@
+use super::{Map, MyTurn, OtherLevelPosition, ParticleLifetime, Player, Position, TileType};
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+use std::collections::HashMap;
+
+/// Every level the player has visited, by depth. Entities left behind on those levels stay
+/// in the world with an `OtherLevelPosition` instead of a `Position`, so nothing acts on
+/// them until the player comes back.
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct MasterDungeonMap {
+    maps: HashMap<i32, Map>,
+}
+
+impl MasterDungeonMap {
+    pub fn store_map(&mut self, map: &Map) {
+        self.maps.insert(map.depth, map.clone());
+    }
+
+    /// A copy of the level at `depth`, ready to be made current, if it has been visited.
+    pub fn get_map(&self, depth: i32) -> Option<Map> {
+        self.maps.get(&depth).map(|map| {
+            let mut map = map.clone();
+            map.tile_content = vec![Vec::new(); (map.width * map.height) as usize];
+            map
+        })
+    }
+}
+
+/// Puts everything on the current level except the player into storage for later, giving up
+/// any turn it was holding. Particles are only for show, so they are dropped instead.
+pub fn freeze_level_entities(ecs: &mut World) {
+    let depth = ecs.fetch::<Map>().depth;
+    let entities = ecs.entities();
+    let players = ecs.read_storage::<Player>();
+    let particles = ecs.read_storage::<ParticleLifetime>();
+    let mut positions = ecs.write_storage::<Position>();
+    let mut other_level_positions = ecs.write_storage::<OtherLevelPosition>();
+    let mut my_turn = ecs.write_storage::<MyTurn>();
+
+    let mut on_level = Vec::new();
+    for (entity, pos, _player) in (&entities, &positions, !&players).join() {
+        on_level.push((entity, pos.x, pos.y));
+    }
+    for (entity, x, y) in on_level {
+        positions.remove(entity);
+        my_turn.remove(entity);
+        if particles.get(entity).is_some() {
+            entities.delete(entity).expect("Unable to delete particle");
+        } else {
+            other_level_positions
+                .insert(entity, OtherLevelPosition { x, y, depth })
+                .expect("Unable to store entity");
+        }
+    }
+}
+
+/// Brings back everything that was left on the level at `depth`.
+pub fn thaw_level_entities(ecs: &mut World, depth: i32) {
+    let entities = ecs.entities();
+    let mut positions = ecs.write_storage::<Position>();
+    let mut other_level_positions = ecs.write_storage::<OtherLevelPosition>();
+
+    let mut on_level = Vec::new();
+    for (entity, pos) in (&entities, &other_level_positions).join() {
+        if pos.depth == depth {
+            on_level.push((entity, pos.x, pos.y));
+        }
+    }
+    for (entity, x, y) in on_level {
+        other_level_positions.remove(entity);
+        positions
+            .insert(entity, Position { x, y })
+            .expect("Unable to restore entity");
+    }
+}
+
+/// Where the player arrives on a level they have been to before: on its up stairs when
+/// coming down, on its down stairs when coming up.
+pub fn arrival_point(map: &Map, going_down: bool) -> Option<(i32, i32)> {
+    let stairs = if going_down {
+        TileType::UpStairs
+    } else {
+        TileType::DownStairs
+    };
+    map.tiles
+        .iter()
+        .position(|tile| *tile == stairs)
+        .map(|idx| (idx as i32 % map.width, idx as i32 / map.width))
+}
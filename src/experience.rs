@@ -0,0 +1,91 @@
+use super::{
+    attributes::{self, Attribute, Skill},
+    gamelog::{GameLog, LogCategory, LogEntry},
+    Attributes, CombatStats, Experience, Skills,
+};
+use specs::prelude::*;
+
+/// Experience it takes to get from `level` to the next one.
+pub fn xp_to_next_level(level: i32) -> i32 {
+    level * 100
+}
+
+/// What a new level improves, on top of the hit points every level brings; the player
+/// picks one each time.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum LevelUpChoice {
+    Attribute(Attribute),
+    Skill(Skill),
+}
+
+impl LevelUpChoice {
+    pub const ALL: [LevelUpChoice; 8] = [
+        LevelUpChoice::Attribute(Attribute::Might),
+        LevelUpChoice::Attribute(Attribute::Agility),
+        LevelUpChoice::Attribute(Attribute::Toughness),
+        LevelUpChoice::Attribute(Attribute::Intellect),
+        LevelUpChoice::Skill(Skill::Melee),
+        LevelUpChoice::Skill(Skill::Defense),
+        LevelUpChoice::Skill(Skill::Magic),
+        LevelUpChoice::Skill(Skill::Stealth),
+    ];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            LevelUpChoice::Attribute(Attribute::Might) => "Might +1: melee damage",
+            LevelUpChoice::Attribute(Attribute::Agility) => "Agility +1: to-hit and dodge",
+            LevelUpChoice::Attribute(Attribute::Toughness) => "Toughness +1: hit points",
+            LevelUpChoice::Attribute(Attribute::Intellect) => "Intellect +1: magic damage",
+            LevelUpChoice::Skill(Skill::Melee) => "Melee +1: to-hit",
+            LevelUpChoice::Skill(Skill::Defense) => "Defense +1: dodge",
+            LevelUpChoice::Skill(Skill::Magic) => "Magic +1: magic damage",
+            LevelUpChoice::Skill(Skill::Stealth) => "Stealth +1: harder to notice",
+        }
+    }
+}
+
+/// Whether the player has earned another level and hasn't picked what it improves yet.
+pub fn level_up_pending(ecs: &World) -> bool {
+    let player_entity = *ecs.fetch::<Entity>();
+    ecs.read_storage::<Experience>()
+        .get(player_entity)
+        .is_some_and(|exp| exp.xp >= xp_to_next_level(exp.level))
+}
+
+/// Spends the player's experience on the next level, improving `choice`, rederiving their
+/// hit points and restoring them to full.
+pub fn level_up(ecs: &mut World, choice: LevelUpChoice) {
+    let player_entity = *ecs.fetch::<Entity>();
+    let mut experience = ecs.write_storage::<Experience>();
+    let mut combat_stats = ecs.write_storage::<CombatStats>();
+    let mut attributes = ecs.write_storage::<Attributes>();
+    let mut skills = ecs.write_storage::<Skills>();
+    let (exp, stats, attributes, skills) = match (
+        experience.get_mut(player_entity),
+        combat_stats.get_mut(player_entity),
+        attributes.get_mut(player_entity),
+        skills.get_mut(player_entity),
+    ) {
+        (Some(exp), Some(stats), Some(attributes), Some(skills)) => {
+            (exp, stats, attributes, skills)
+        }
+        _ => return,
+    };
+
+    exp.xp -= xp_to_next_level(exp.level);
+    exp.level += 1;
+    match choice {
+        LevelUpChoice::Attribute(attribute) => *attributes.get_mut(attribute) += 1,
+        LevelUpChoice::Skill(skill) => *skills.get_mut(skill) += 1,
+    }
+    stats.max_hp = attributes::max_hp(attributes, exp.level);
+    stats.hp = stats.max_hp;
+
+    ecs.write_resource::<GameLog>().push(
+        LogEntry::new(LogCategory::System)
+            .text("Welcome to level ")
+            .colored(exp.level, rltk::RGB::named(rltk::CYAN))
+            .text("! ")
+            .text(choice.description()),
+    );
+}
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+/// Game time for the current run. A turn is one player action; a tick is one pass of the
+/// systems, so the monsters' moves after each turn take ticks of their own. Systems can read
+/// it for anything timed.
+#[derive(Serialize, Deserialize, Copy, Clone, Default, Debug, PartialEq)]
+pub struct GameClock {
+    pub turn: u32,
+    pub tick: u64,
+}
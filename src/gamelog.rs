@@ -1,3 +1,127 @@
+use rltk::RGB;
+use std::collections::VecDeque;
+
+/// How many entries the log keeps; older ones fall off the front.
+pub const MAX_LOG_ENTRIES: usize = 500;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum LogCategory {
+    Combat,
+    Item,
+    Hunger,
+    Trap,
+    Status,
+    System,
+}
+
+impl LogCategory {
+    /// Colour for the plain text of an entry in this category.
+    pub fn color(self) -> RGB {
+        match self {
+            LogCategory::Combat => RGB::named(rltk::WHITE),
+            LogCategory::Item => RGB::named(rltk::LIGHT_GREEN),
+            LogCategory::Hunger => RGB::named(rltk::ORANGE),
+            LogCategory::Trap => RGB::named(rltk::LIGHT_SALMON),
+            LogCategory::Status => RGB::named(rltk::PLUM),
+            LogCategory::System => RGB::named(rltk::LIGHT_GRAY),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct LogSpan {
+    pub text: String,
+    pub color: RGB,
+}
+
+/// One line of the log, built up span by span:
+/// `LogEntry::new(LogCategory::Combat).name("Orc").text(" hits you, for ").damage(3)`.
+/// `count` is how many times in a row it happened.
+#[derive(PartialEq, Clone, Debug)]
+pub struct LogEntry {
+    pub category: LogCategory,
+    pub spans: Vec<LogSpan>,
+    pub count: u32,
+}
+
+impl LogEntry {
+    pub fn new(category: LogCategory) -> LogEntry {
+        LogEntry {
+            category,
+            spans: Vec::new(),
+            count: 1,
+        }
+    }
+
+    pub fn text<S: ToString>(self, text: S) -> LogEntry {
+        let color = self.category.color();
+        self.colored(text, color)
+    }
+
+    pub fn name<S: ToString>(self, name: S) -> LogEntry {
+        self.colored(name, RGB::named(rltk::YELLOW))
+    }
+
+    pub fn damage(self, amount: i32) -> LogEntry {
+        self.colored(amount, RGB::named(rltk::RED))
+    }
+
+    pub fn colored<S: ToString>(mut self, text: S, color: RGB) -> LogEntry {
+        self.spans.push(LogSpan {
+            text: text.to_string(),
+            color,
+        });
+        self
+    }
+
+    /// The entry as plain text, with the repeat count if there is one.
+    pub fn plain_text(&self) -> String {
+        let mut text: String = self.spans.iter().map(|span| span.text.as_str()).collect();
+        if self.count > 1 {
+            text.push_str(&format!(" x{}", self.count));
+        }
+        text
+    }
+}
+
 pub struct GameLog {
-    pub entries: Vec<String>,
+    entries: VecDeque<LogEntry>,
+}
+
+impl GameLog {
+    pub fn new() -> GameLog {
+        GameLog {
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// Adds an entry. The same line twice in a row bumps the count on the first instead.
+    pub fn push(&mut self, entry: LogEntry) {
+        if let Some(last) = self.entries.back_mut() {
+            if last.category == entry.category && last.spans == entry.spans {
+                last.count += entry.count;
+                return;
+            }
+        }
+        self.entries.push_back(entry);
+        while self.entries.len() > MAX_LOG_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Adds a line of plain text.
+    pub fn say<S: ToString>(&mut self, category: LogCategory, text: S) {
+        self.push(LogEntry::new(category).text(text));
+    }
+
+    /// Entries from oldest to newest.
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &LogEntry> + ExactSizeIterator {
+        self.entries.iter()
+    }
+}
+
+impl Default for GameLog {
+    fn default() -> GameLog {
+        GameLog::new()
+    }
 }
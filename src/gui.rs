@@ -1,8 +1,16 @@
+use crate::attributes::{self, attribute_bonus, Attribute, Skill};
+use crate::camera::Camera;
+use crate::combat;
+use crate::experience::{xp_to_next_level, LevelUpChoice};
 use crate::rex_assets::RexAssets;
+use crate::saveload_system::{format_timestamp, LoadFailure, SaveSlots, SlotStatus};
 
 use super::{
-    gamelog::GameLog, CombatStats, Equipped, Hidden, HungerClock, HungerState, InBackpack, Map,
-    Name, Player, Position, RunState, State, Viewshed,
+    game_clock::GameClock,
+    gamelog::{GameLog, LogEntry},
+    Attributes, CombatStats, DefenseBonus, Equipped, Experience, Hidden, HungerClock, HungerState,
+    InBackpack, Map, MeleeWeapon, Name, Player, Position, RunSeed, RunState, Skills, State,
+    StatusEffects, Viewshed,
 };
 use rltk::{Point, Rltk, VirtualKeyCode, RGB};
 use specs::prelude::*;
@@ -65,13 +73,45 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
         }
     }
 
-    let log = ecs.fetch::<GameLog>();
-    let mut y = 44;
-    for s in log.entries.iter().rev() {
-        if y < 49 {
-            ctx.print(2, y, s);
+    // Status effects line up leftwards from the hunger state, with the turns they have left.
+    // When they won't all fit they are cut to three letters, and any still past the left
+    // edge are left off.
+    if let Some(effects) = ecs
+        .read_storage::<StatusEffects>()
+        .get(*ecs.fetch::<Entity>())
+    {
+        let (left, right) = (1, 70);
+        let mut labels: Vec<String> = effects
+            .effects
+            .iter()
+            .map(|effect| format!("{} {}", effect.kind.name(), effect.turns))
+            .collect();
+        if labels
+            .iter()
+            .map(|label| label.len() as i32 + 1)
+            .sum::<i32>()
+            > right - left
+        {
+            labels = effects
+                .effects
+                .iter()
+                .map(|effect| format!("{} {}", &effect.kind.name()[..3], effect.turns))
+                .collect();
         }
-        y += 1;
+        let mut x = right;
+        for (effect, mut label) in effects.effects.iter().zip(labels).rev() {
+            label[..1].make_ascii_uppercase();
+            x -= label.len() as i32 + 1;
+            if x < left {
+                break;
+            }
+            ctx.print_color(x, 42, effect.kind.color(), RGB::named(rltk::BLACK), &label);
+        }
+    }
+
+    let log = ecs.fetch::<GameLog>();
+    for (i, entry) in log.entries().rev().take(5).enumerate() {
+        print_log_entry(ctx, 2, 44 + i as i32, 76, entry);
     }
 
     let map = ecs.fetch::<Map>();
@@ -84,6 +124,41 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
         &depth,
     );
 
+    let turn = format!("Turn: {}", ecs.fetch::<GameClock>().turn);
+    ctx.print_color(
+        2,
+        49,
+        RGB::named(rltk::GRAY),
+        RGB::named(rltk::BLACK),
+        &turn,
+    );
+
+    let player_entity = *ecs.fetch::<Entity>();
+    if let Some(exp) = ecs.read_storage::<Experience>().get(player_entity) {
+        let level = format!(
+            "Level {}  XP {}/{}",
+            exp.level,
+            exp.xp,
+            xp_to_next_level(exp.level)
+        );
+        ctx.print_color(
+            16,
+            49,
+            RGB::named(rltk::CYAN),
+            RGB::named(rltk::BLACK),
+            &level,
+        );
+    }
+
+    let seed = format!("Seed: {}", ecs.fetch::<RunSeed>().seed);
+    ctx.print_color(
+        78 - seed.len() as i32,
+        49,
+        RGB::named(rltk::GRAY),
+        RGB::named(rltk::BLACK),
+        &seed,
+    );
+
     // Draw mouse cursor
     let mouse_pos = ctx.mouse_pos();
     ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::MAGENTA));
@@ -97,13 +172,15 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     let hidden = ecs.read_storage::<Hidden>();
 
     let mouse_pos = ctx.mouse_pos();
-    if mouse_pos.0 >= map.width || mouse_pos.1 >= map.height {
-        return;
-    }
+    let mouse_world =
+        match Camera::for_player(ecs).screen_to_world(Point::new(mouse_pos.0, mouse_pos.1), &map) {
+            None => return,
+            Some(world) => world,
+        };
     let mut tooltip: Vec<String> = Vec::new();
     for (name, position, _hidden) in (&names, &positions, !&hidden).join() {
         let idx = map.xy_idx(position.x, position.y);
-        if position.x == mouse_pos.0 && position.y == mouse_pos.1 && map.visible_tiles[idx] {
+        if position.x == mouse_world.x && position.y == mouse_world.y && map.visible_tiles[idx] {
             tooltip.push(name.name.to_string());
         }
     }
@@ -120,8 +197,7 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
         if mouse_pos.0 > 40 {
             let arrow_pos = Point::new(mouse_pos.0 - 2, mouse_pos.1);
             let left_x = mouse_pos.0 - width;
-            let mut y = mouse_pos.1;
-            for s in tooltip.iter() {
+            for (y, s) in (mouse_pos.1..).zip(tooltip.iter()) {
                 ctx.print_color(
                     left_x,
                     y,
@@ -136,23 +212,21 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
                         y,
                         RGB::named(rltk::WHITE),
                         RGB::named(rltk::GREY),
-                        &" ".to_string(),
+                        " ",
                     );
                 }
-                y += 1;
             }
             ctx.print_color(
                 arrow_pos.x,
                 arrow_pos.y,
                 RGB::named(rltk::WHITE),
                 RGB::named(rltk::GREY),
-                &"->".to_string(),
+                "->",
             );
         } else {
             let arrow_pos = Point::new(mouse_pos.0 + 1, mouse_pos.1);
             let left_x = mouse_pos.0 + 3;
-            let mut y = mouse_pos.1;
-            for s in tooltip.iter() {
+            for (y, s) in (mouse_pos.1..).zip(tooltip.iter()) {
                 ctx.print_color(
                     left_x + 1,
                     y,
@@ -167,17 +241,16 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
                         y,
                         RGB::named(rltk::WHITE),
                         RGB::named(rltk::GREY),
-                        &" ".to_string(),
+                        " ",
                     );
                 }
-                y += 1;
             }
             ctx.print_color(
                 arrow_pos.x,
                 arrow_pos.y,
                 RGB::named(rltk::WHITE),
                 RGB::named(rltk::GREY),
-                &"<-".to_string(),
+                "<-",
             );
         }
     }
@@ -197,7 +270,7 @@ pub enum MainMenuResult {
 }
 
 pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
-    let save_exists = super::saveload_system::does_save_exist();
+    let save_exists = gs.ecs.fetch::<SaveSlots>().any_used();
     let runstate = gs.ecs.fetch::<RunState>();
 
     let assets = gs.ecs.fetch::<RexAssets>();
@@ -282,6 +355,11 @@ pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
             ctx.print_color_centered(y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), "Quit");
         }
 
+        if let Some(failure) = gs.ecs.try_fetch::<LoadFailure>() {
+            let message: String = failure.message.chars().take(78).collect();
+            ctx.print_color_centered(30, RGB::named(rltk::RED), RGB::named(rltk::BLACK), &message);
+        }
+
         match ctx.key {
             None => {
                 return MainMenuResult::NoSelection {
@@ -341,6 +419,480 @@ pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
     }
 }
 
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum SlotMenuMode {
+    Save,
+    Load,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum SlotMenuResult {
+    NoSelection { selected: usize },
+    Selected { selected: usize },
+    ToggleFormat,
+    Cancel,
+}
+
+/// The save slots as they were when the slot menu opened.
+pub struct SlotListing {
+    pub slots: Vec<SlotStatus>,
+}
+
+fn slot_description(status: &SlotStatus) -> String {
+    match status {
+        SlotStatus::Empty => "- empty -".to_string(),
+        SlotStatus::Saved(None) => "Saved game (no details)".to_string(),
+        SlotStatus::Saved(Some(summary)) => format!(
+            "{:<12} Depth {:<3} HP {:>3}/{:<3} Turn {:<6} {}",
+            summary.name,
+            summary.depth,
+            summary.hp,
+            summary.max_hp,
+            summary.turn,
+            format_timestamp(summary.saved_at)
+        ),
+    }
+}
+
+pub fn slot_menu(gs: &mut State, ctx: &mut Rltk) -> SlotMenuResult {
+    let runstate = *gs.ecs.fetch::<RunState>();
+    let listing = gs.ecs.fetch::<SlotListing>();
+    let (mode, selection) = match runstate {
+        RunState::SlotMenu { mode, selection } => (mode, selection),
+        _ => return SlotMenuResult::Cancel,
+    };
+    let selectable = |slot: usize| match mode {
+        SlotMenuMode::Save => true,
+        SlotMenuMode::Load => listing.slots[slot] != SlotStatus::Empty,
+    };
+
+    let count = listing.slots.len() as i32;
+    let top = 20 - count / 2;
+    ctx.draw_box(
+        4,
+        top - 2,
+        71,
+        count + 3,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    let title = match mode {
+        SlotMenuMode::Save => "Save to which slot?",
+        SlotMenuMode::Load => "Load which game?",
+    };
+    ctx.print_color(
+        7,
+        top - 2,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        title,
+    );
+    ctx.print_color(
+        7,
+        top + count + 1,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "ESCAPE to cancel",
+    );
+    if mode == SlotMenuMode::Save {
+        let format = format!(
+            "Format: {} (F to change)",
+            gs.ecs.fetch::<SaveSlots>().format.name()
+        );
+        ctx.print_color(
+            40,
+            top + count + 1,
+            RGB::named(rltk::YELLOW),
+            RGB::named(rltk::BLACK),
+            &format,
+        );
+    }
+
+    for (slot, status) in listing.slots.iter().enumerate() {
+        let fg = if slot == selection {
+            RGB::named(rltk::MAGENTA)
+        } else if selectable(slot) {
+            RGB::named(rltk::WHITE)
+        } else {
+            RGB::named(rltk::GRAY)
+        };
+        let line = format!("{}. {}", slot + 1, slot_description(status));
+        ctx.print_color(6, top + slot as i32, fg, RGB::named(rltk::BLACK), &line);
+    }
+
+    let step = |delta: usize| {
+        let len = listing.slots.len();
+        (1..=len)
+            .map(|i| (selection + i * delta) % len)
+            .find(|slot| selectable(*slot))
+            .unwrap_or(selection)
+    };
+    match ctx.key {
+        None => SlotMenuResult::NoSelection {
+            selected: selection,
+        },
+        Some(key) => match key {
+            VirtualKeyCode::Escape => SlotMenuResult::Cancel,
+            VirtualKeyCode::Up => SlotMenuResult::NoSelection {
+                selected: step(listing.slots.len() - 1),
+            },
+            VirtualKeyCode::Down => SlotMenuResult::NoSelection { selected: step(1) },
+            VirtualKeyCode::F if mode == SlotMenuMode::Save => SlotMenuResult::ToggleFormat,
+            VirtualKeyCode::Return if selectable(selection) => SlotMenuResult::Selected {
+                selected: selection,
+            },
+            _ => SlotMenuResult::NoSelection {
+                selected: selection,
+            },
+        },
+    }
+}
+
+/// Prints `entry` in its colours from (x, y), cut off after `width` characters.
+fn print_log_entry(ctx: &mut Rltk, x: i32, y: i32, width: i32, entry: &LogEntry) {
+    let count = format!(" x{}", entry.count);
+    let mut spans: Vec<(&str, RGB)> = entry
+        .spans
+        .iter()
+        .map(|span| (span.text.as_str(), span.color))
+        .collect();
+    if entry.count > 1 {
+        spans.push((&count, RGB::named(rltk::GRAY)));
+    }
+
+    let mut column = x;
+    for (text, color) in spans {
+        let room = (x + width - column).max(0) as usize;
+        let text: String = text.chars().take(room).collect();
+        ctx.print_color(column, y, color, RGB::named(rltk::BLACK), &text);
+        column += text.chars().count() as i32;
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum LogHistoryResult {
+    NoResponse { offset: usize },
+    Close,
+}
+
+/// The whole message log, newest at the bottom. `offset` is how many entries the view is
+/// scrolled back from the newest.
+pub fn log_history(gs: &mut State, ctx: &mut Rltk) -> LogHistoryResult {
+    const LINES: usize = 46;
+    let runstate = *gs.ecs.fetch::<RunState>();
+    let log = gs.ecs.fetch::<GameLog>();
+    let offset = match runstate {
+        RunState::LogHistory { offset } => offset,
+        _ => return LogHistoryResult::Close,
+    };
+    let max_offset = log.entries().len().saturating_sub(LINES);
+    let offset = offset.min(max_offset);
+
+    ctx.draw_box(
+        0,
+        0,
+        79,
+        49,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color(
+        3,
+        0,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Message Log",
+    );
+    ctx.print_color(
+        3,
+        49,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "ESCAPE to close, arrows and PgUp/PgDn to scroll",
+    );
+
+    let shown: Vec<&LogEntry> = log.entries().rev().skip(offset).take(LINES).collect();
+    for (i, entry) in shown.iter().rev().enumerate() {
+        let y = 2 + (LINES - shown.len() + i) as i32;
+        print_log_entry(ctx, 2, y, 76, entry);
+    }
+
+    let scrolled = |delta: isize| LogHistoryResult::NoResponse {
+        offset: (offset as isize + delta).clamp(0, max_offset as isize) as usize,
+    };
+    match ctx.key {
+        None => LogHistoryResult::NoResponse { offset },
+        Some(key) => match key {
+            VirtualKeyCode::Escape | VirtualKeyCode::M => LogHistoryResult::Close,
+            VirtualKeyCode::Up | VirtualKeyCode::K => scrolled(1),
+            VirtualKeyCode::Down | VirtualKeyCode::J => scrolled(-1),
+            VirtualKeyCode::PageUp => scrolled(LINES as isize),
+            VirtualKeyCode::PageDown => scrolled(-(LINES as isize)),
+            VirtualKeyCode::Home => scrolled(max_offset as isize),
+            VirtualKeyCode::End => scrolled(-(max_offset as isize)),
+            _ => LogHistoryResult::NoResponse { offset },
+        },
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum CharacterSheetResult {
+    NoResponse,
+    Close,
+}
+
+/// The player's attributes and skills, and the combat numbers they work out to.
+pub fn character_sheet(gs: &mut State, ctx: &mut Rltk) -> CharacterSheetResult {
+    let player_entity = *gs.ecs.fetch::<Entity>();
+    let attributes = gs
+        .ecs
+        .read_storage::<Attributes>()
+        .get(player_entity)
+        .cloned()
+        .unwrap_or_default();
+    let skills = gs
+        .ecs
+        .read_storage::<Skills>()
+        .get(player_entity)
+        .cloned()
+        .unwrap_or_default();
+    let (level, xp) = gs
+        .ecs
+        .read_storage::<Experience>()
+        .get(player_entity)
+        .map_or((1, 0), |exp| (exp.level, exp.xp));
+    let (hp, max_hp) = gs
+        .ecs
+        .read_storage::<CombatStats>()
+        .get(player_entity)
+        .map_or((0, 0), |stats| (stats.hp, stats.max_hp));
+
+    ctx.draw_box(
+        15,
+        8,
+        40,
+        31,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color(
+        18,
+        8,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Character",
+    );
+    ctx.print_color(
+        18,
+        39,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "ESCAPE to close",
+    );
+
+    let heading = |ctx: &mut Rltk, y: i32, text: &str| {
+        ctx.print_color(18, y, RGB::named(rltk::CYAN), RGB::named(rltk::BLACK), text);
+    };
+    let row = |ctx: &mut Rltk, y: i32, label: &str, value: String| {
+        ctx.print(20, y, label);
+        ctx.print(38, y, value);
+    };
+
+    row(
+        ctx,
+        10,
+        "Level",
+        format!("{}  ({}/{} XP)", level, xp, xp_to_next_level(level)),
+    );
+    row(ctx, 11, "Hit points", format!("{} / {}", hp, max_hp));
+
+    heading(ctx, 13, "Attributes");
+    for (i, attribute) in Attribute::ALL.iter().enumerate() {
+        let value = attributes.get(*attribute);
+        row(
+            ctx,
+            14 + i as i32,
+            attribute.name(),
+            format!("{:>2} ({:+})", value, attribute_bonus(value)),
+        );
+    }
+
+    heading(ctx, 19, "Skills");
+    for (i, skill) in Skill::ALL.iter().enumerate() {
+        row(
+            ctx,
+            20 + i as i32,
+            skill.name(),
+            format!("{:>2}", skills.get(*skill)),
+        );
+    }
+
+    let mut weapon_damage = combat::UNARMED_DAMAGE;
+    let mut hit_bonus = 0;
+    let mut armor = 0;
+    let equipped = gs.ecs.read_storage::<Equipped>();
+    for (weapon, equipped_by) in (&gs.ecs.read_storage::<MeleeWeapon>(), &equipped).join() {
+        if equipped_by.owner == player_entity {
+            weapon_damage = weapon.damage;
+            hit_bonus += weapon.hit_bonus;
+        }
+    }
+    for (defense, equipped_by) in (&gs.ecs.read_storage::<DefenseBonus>(), &equipped).join() {
+        if equipped_by.owner == player_entity {
+            armor += defense.defense;
+        }
+    }
+    weapon_damage.bonus += attributes::melee_damage(&attributes);
+
+    heading(ctx, 25, "Combat");
+    let derived = [
+        (
+            "To-hit",
+            format!("{:+}", attributes::to_hit(&attributes, &skills) + hit_bonus),
+        ),
+        ("Damage", combat::dice_string(weapon_damage)),
+        (
+            "Armor class",
+            combat::armor_class(attributes::dodge(&attributes, &skills), armor).to_string(),
+        ),
+        (
+            "Magic damage",
+            format!("{:+}", attributes::spell_damage(&attributes, &skills)),
+        ),
+        (
+            "HP per level",
+            format!("{:+}", attributes::hp_per_level(&attributes)),
+        ),
+    ];
+    for (i, (label, value)) in derived.iter().enumerate() {
+        row(ctx, 26 + i as i32, label, value.clone());
+    }
+
+    match ctx.key {
+        Some(VirtualKeyCode::Escape) | Some(VirtualKeyCode::C) => CharacterSheetResult::Close,
+        _ => CharacterSheetResult::NoResponse,
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum LevelUpResult {
+    NoResponse,
+    Selected(LevelUpChoice),
+}
+
+pub fn level_up_menu(gs: &mut State, ctx: &mut Rltk) -> LevelUpResult {
+    let player_entity = *gs.ecs.fetch::<Entity>();
+    let level = gs
+        .ecs
+        .read_storage::<Experience>()
+        .get(player_entity)
+        .map_or(1, |exp| exp.level);
+    let count = LevelUpChoice::ALL.len() as i32;
+
+    let top = 25 - count / 2;
+    ctx.draw_box(
+        15,
+        top - 2,
+        40,
+        count + 3,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color(
+        18,
+        top - 2,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        format!("Level {}! Choose an improvement", level + 1),
+    );
+    for (j, choice) in LevelUpChoice::ALL.iter().enumerate() {
+        let y = top + j as i32;
+        ctx.set(
+            17,
+            y,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            rltk::to_cp437('('),
+        );
+        ctx.set(
+            18,
+            y,
+            RGB::named(rltk::YELLOW),
+            RGB::named(rltk::BLACK),
+            97 + j as rltk::FontCharType,
+        );
+        ctx.set(
+            19,
+            y,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            rltk::to_cp437(')'),
+        );
+        ctx.print(21, y, choice.description());
+    }
+
+    match ctx.key {
+        None => LevelUpResult::NoResponse,
+        Some(key) => {
+            let selection = rltk::letter_to_option(key);
+            if selection > -1 && selection < count {
+                LevelUpResult::Selected(LevelUpChoice::ALL[selection as usize])
+            } else {
+                LevelUpResult::NoResponse
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum CrashRecoveryResult {
+    NoResponse,
+    Recover,
+    Later,
+}
+
+pub fn crash_recovery(gs: &mut State, ctx: &mut Rltk) -> CrashRecoveryResult {
+    let runstate = *gs.ecs.fetch::<RunState>();
+    let listing = gs.ecs.fetch::<SlotListing>();
+    let slot = match runstate {
+        RunState::CrashRecovery { slot } => slot,
+        _ => return CrashRecoveryResult::Later,
+    };
+
+    ctx.draw_box_double(
+        4,
+        16,
+        71,
+        8,
+        RGB::named(rltk::WHEAT),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color_centered(
+        18,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Your last game didn't end properly.",
+    );
+    ctx.print_color_centered(
+        20,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+        format!("{}. {}", slot + 1, slot_description(&listing.slots[slot])),
+    );
+    ctx.print_color_centered(
+        22,
+        RGB::named(rltk::GRAY),
+        RGB::named(rltk::BLACK),
+        "ENTER to carry on from the autosave, ESCAPE for the menu",
+    );
+
+    match ctx.key {
+        Some(VirtualKeyCode::Return) => CrashRecoveryResult::Recover,
+        Some(VirtualKeyCode::Escape) => CrashRecoveryResult::Later,
+        _ => CrashRecoveryResult::NoResponse,
+    }
+}
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum ItemMenuResult {
     Cancel,
@@ -359,10 +911,10 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
         .filter(|item| item.0.owner == *player_entity);
     let count = inventory.count();
 
-    let mut y = (25 - (count / 2)) as i32;
+    let top = (25 - (count / 2)) as i32;
     ctx.draw_box(
         15,
-        y - 2,
+        top - 2,
         31,
         (count + 3) as i32,
         RGB::named(rltk::WHITE),
@@ -370,25 +922,26 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
     );
     ctx.print_color(
         18,
-        y - 2,
+        top - 2,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
         "Inventory",
     );
     ctx.print_color(
         18,
-        y + count as i32 + 1,
+        top + count as i32 + 1,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
         "ESCAPE to cancel",
     );
 
     let mut equipable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names)
+    for (j, (entity, _pack, name)) in (&entities, &backpack, &names)
         .join()
         .filter(|item| item.1.owner == *player_entity)
+        .enumerate()
     {
+        let y = top + j as i32;
         ctx.set(
             17,
             y,
@@ -411,10 +964,8 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
             rltk::to_cp437(')'),
         );
 
-        ctx.print(21, y, &name.name.to_string());
+        ctx.print(21, y, &name.name);
         equipable.push(entity);
-        y += 1;
-        j += 1;
     }
 
     match ctx.key {
@@ -446,10 +997,10 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
         .filter(|item| item.0.owner == *player_entity);
     let count = inventory.count();
 
-    let mut y = (25 - (count / 2)) as i32;
+    let top = (25 - (count / 2)) as i32;
     ctx.draw_box(
         15,
-        y - 2,
+        top - 2,
         31,
         (count + 3) as i32,
         RGB::named(rltk::WHITE),
@@ -457,25 +1008,26 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
     );
     ctx.print_color(
         18,
-        y - 2,
+        top - 2,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
         "Drop Which Item?",
     );
     ctx.print_color(
         18,
-        y + count as i32 + 1,
+        top + count as i32 + 1,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
         "ESCAPE to cancel",
     );
 
     let mut equippable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names)
+    for (j, (entity, _pack, name)) in (&entities, &backpack, &names)
         .join()
         .filter(|item| item.1.owner == *player_entity)
+        .enumerate()
     {
+        let y = top + j as i32;
         ctx.set(
             17,
             y,
@@ -498,10 +1050,8 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
             rltk::to_cp437(')'),
         );
 
-        ctx.print(21, y, &name.name.to_string());
+        ctx.print(21, y, &name.name);
         equippable.push(entity);
-        y += 1;
-        j += 1;
     }
 
     match ctx.key {
@@ -533,10 +1083,10 @@ pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Opti
         .filter(|item| item.0.owner == *player_entity);
     let count = inventory.count();
 
-    let mut y = 25 - (count / 2) as i32;
+    let top = 25 - (count / 2) as i32;
     ctx.draw_box(
         15,
-        y - 2,
+        top - 2,
         31,
         (count + 3) as i32,
         RGB::named(rltk::WHITE),
@@ -544,25 +1094,26 @@ pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Opti
     );
     ctx.print_color(
         18,
-        y - 2,
+        top - 2,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
         "Remove which item?",
     );
     ctx.print_color(
         18,
-        y + count as i32 + 1,
+        top + count as i32 + 1,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
         "ESCAPE to cancel",
     );
 
     let mut equippable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names)
+    for (j, (entity, _pack, name)) in (&entities, &backpack, &names)
         .join()
         .filter(|item| item.1.owner == *player_entity)
+        .enumerate()
     {
+        let y = top + j as i32;
         ctx.set(
             17,
             y,
@@ -585,10 +1136,8 @@ pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Opti
             rltk::to_cp437(')'),
         );
 
-        ctx.print(21, y, &name.name.to_string());
+        ctx.print(21, y, &name.name);
         equippable.push(entity);
-        y += 1;
-        j += 1;
     }
 
     match ctx.key {
@@ -626,13 +1175,18 @@ pub fn ranged_target(
         "Target:",
     );
 
+    let map = gs.ecs.fetch::<Map>();
+    let camera = Camera::for_player(&gs.ecs);
+
     let mut available_cells = Vec::new();
     let visible = viewsheds.get(*player_entity);
     if let Some(visible) = visible {
         for idx in visible.visible_tiles.iter() {
             let distance = rltk::DistanceAlg::Pythagoras.distance2d(*player_pos, *idx);
             if distance <= range as f32 {
-                ctx.set_bg(idx.x, idx.y, RGB::named(rltk::BLUE));
+                if let Some(screen) = camera.world_to_screen(*idx) {
+                    ctx.set_bg(screen.x, screen.y, RGB::named(rltk::BLUE));
+                }
                 available_cells.push(idx);
             }
         }
@@ -641,19 +1195,17 @@ pub fn ranged_target(
     }
 
     let mouse_pos = ctx.mouse_pos();
+    let mouse_world = camera.screen_to_world(Point::new(mouse_pos.0, mouse_pos.1), &map);
     let mut valid_target = false;
     for idx in available_cells.iter() {
-        if idx.x == mouse_pos.0 && idx.y == mouse_pos.1 {
+        if Some(**idx) == mouse_world {
             valid_target = true;
         }
     }
     if valid_target {
         ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::CYAN));
         if ctx.left_click {
-            return (
-                ItemMenuResult::Selected,
-                Some(Point::new(mouse_pos.0, mouse_pos.1)),
-            );
+            return (ItemMenuResult::Selected, mouse_world);
         }
     } else {
         ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::RED));
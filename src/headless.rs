@@ -0,0 +1,163 @@
+use super::{
+    damage_system, experience::LevelUpChoice, particle_system, player, saveload_system, RunState,
+    State, WantsToDropItem, WantsToRemoveItem, WantsToUseItem,
+};
+use rltk::Point;
+use specs::prelude::*;
+
+/// Simulated frame time used to age particles, matching a 60fps window.
+const FRAME_TIME_MS: f32 = 1000.0 / 60.0;
+
+/// A player action, the headless equivalent of a key press in `player_input`.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Command {
+    Move {
+        delta_x: i32,
+        delta_y: i32,
+    },
+    Wait,
+    PickUp,
+    UseItem {
+        item: Entity,
+        target: Option<Point>,
+    },
+    DropItem {
+        item: Entity,
+    },
+    RemoveItem {
+        item: Entity,
+    },
+    Descend,
+    Ascend,
+    /// Only accepted while the game is waiting for a level-up choice.
+    LevelUp {
+        choice: LevelUpChoice,
+    },
+}
+
+/// Drives a `State` without an Rltk window, stepping the run loop exactly like
+/// `GameState::tick` does but taking its input from scripted commands.
+pub struct HeadlessRunner {
+    pub state: State,
+}
+
+impl HeadlessRunner {
+    pub fn new(seed: u64) -> HeadlessRunner {
+        let mut runner = HeadlessRunner {
+            state: State::new(seed),
+        };
+        // Scripted runs stay off the disk unless a caller turns autosaving back on
+        runner
+            .state
+            .ecs
+            .write_resource::<saveload_system::SaveSlots>()
+            .autosave = false;
+        runner.set_runstate(RunState::PreRun);
+        runner.run_until_input();
+        runner
+    }
+
+    pub fn runstate(&self) -> RunState {
+        *self.state.ecs.fetch::<RunState>()
+    }
+
+    pub fn player(&self) -> Entity {
+        *self.state.ecs.fetch::<Entity>()
+    }
+
+    /// Performs one player command and runs the world until it waits for input again.
+    /// Commands are ignored unless the game is waiting for input, and a level-up choice is
+    /// the only command taken while it waits for one.
+    pub fn submit(&mut self, command: Command) -> RunState {
+        match (self.runstate(), command) {
+            (RunState::LevelUp, Command::LevelUp { .. }) => {}
+            (RunState::LevelUp, _) | (_, Command::LevelUp { .. }) => return self.runstate(),
+            (RunState::AwaitingInput, _) => {}
+            _ => return self.runstate(),
+        }
+
+        let newrunstate = self.apply(command);
+        self.set_runstate(newrunstate);
+        self.run_until_input()
+    }
+
+    /// Submits every command in order, stopping early if the player dies.
+    pub fn run_script(&mut self, commands: &[Command]) -> RunState {
+        for command in commands.iter() {
+            if self.submit(*command) == RunState::GameOver {
+                break;
+            }
+        }
+        self.runstate()
+    }
+
+    fn apply(&mut self, command: Command) -> RunState {
+        if let Command::LevelUp { choice } = command {
+            return self.state.choose_level_up(choice);
+        }
+        let ecs = &mut self.state.ecs;
+        match command {
+            Command::Move { delta_x, delta_y } => player::try_move_player(delta_x, delta_y, ecs),
+            Command::Wait => return player::skip_turn(ecs),
+            Command::PickUp => player::get_item(ecs),
+            Command::UseItem { item, target } => {
+                let player_entity = *ecs.fetch::<Entity>();
+                ecs.write_storage::<WantsToUseItem>()
+                    .insert(player_entity, WantsToUseItem { item, target })
+                    .expect("Unable to insert intent");
+            }
+            Command::DropItem { item } => {
+                let player_entity = *ecs.fetch::<Entity>();
+                ecs.write_storage::<WantsToDropItem>()
+                    .insert(player_entity, WantsToDropItem { item })
+                    .expect("Unable to insert intent");
+            }
+            Command::RemoveItem { item } => {
+                let player_entity = *ecs.fetch::<Entity>();
+                ecs.write_storage::<WantsToRemoveItem>()
+                    .insert(player_entity, WantsToRemoveItem { item })
+                    .expect("Unable to insert intent");
+            }
+            Command::Descend => {
+                if player::try_next_level(ecs) {
+                    return RunState::NextLevel;
+                }
+            }
+            Command::Ascend => {
+                if player::try_previous_level(ecs) {
+                    return RunState::PreviousLevel;
+                }
+            }
+            Command::LevelUp { .. } => {}
+        }
+        RunState::PlayerTurn
+    }
+
+    fn run_until_input(&mut self) -> RunState {
+        loop {
+            let runstate = self.runstate();
+            match runstate {
+                RunState::PreRun
+                | RunState::Ticking
+                | RunState::PlayerTurn
+                | RunState::MonsterTurn
+                | RunState::RevealingMap { .. }
+                | RunState::NextLevel
+                | RunState::PreviousLevel => {
+                    particle_system::age_particles(&mut self.state.ecs, FRAME_TIME_MS);
+                    let newrunstate = self.state.advance(runstate);
+                    self.set_runstate(newrunstate);
+                }
+                _ => return runstate,
+            }
+        }
+    }
+
+    fn set_runstate(&mut self, newrunstate: RunState) {
+        {
+            let mut runwriter = self.state.ecs.write_resource::<RunState>();
+            *runwriter = newrunstate;
+        }
+        damage_system::delete_the_dead(&mut self.state.ecs);
+    }
+}
@@ -1,6 +1,9 @@
 use specs::prelude::*;
 
-use super::{gamelog::GameLog, HungerClock, HungerState, RunState, SufferDamage};
+use super::{
+    gamelog::{GameLog, LogCategory},
+    HungerClock, HungerState, MyTurn, RunState, SufferDamage,
+};
 
 pub struct HungerSystem {}
 
@@ -13,13 +16,21 @@ impl<'a> System<'a> for HungerSystem {
         ReadExpect<'a, RunState>,
         WriteStorage<'a, SufferDamage>,
         WriteExpect<'a, GameLog>,
+        ReadStorage<'a, MyTurn>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut hunger_clock, player_entity, runstate, mut inflict_damage, mut log) =
-            data;
+        let (
+            entities,
+            mut hunger_clock,
+            player_entity,
+            runstate,
+            mut inflict_damage,
+            mut log,
+            my_turn,
+        ) = data;
 
-        for (entity, mut clock) in (&entities, &mut hunger_clock).join() {
+        for (entity, clock) in (&entities, &mut hunger_clock).join() {
             let mut proceed = false;
 
             match *runstate {
@@ -29,7 +40,7 @@ impl<'a> System<'a> for HungerSystem {
                     }
                 }
                 RunState::MonsterTurn => {
-                    if entity != *player_entity {
+                    if entity != *player_entity && my_turn.contains(entity) {
                         proceed = true;
                     }
                 }
@@ -44,30 +55,31 @@ impl<'a> System<'a> for HungerSystem {
                             clock.state = HungerState::Normal;
                             clock.duration = 200;
                             if entity == *player_entity {
-                                log.entries.push("You are no longer well fed.".to_string());
+                                log.say(LogCategory::Hunger, "You are no longer well fed.");
                             }
                         }
                         HungerState::Normal => {
                             clock.state = HungerState::Hungry;
                             clock.duration = 200;
                             if entity == *player_entity {
-                                log.entries.push("You are hungy.".to_string());
+                                log.say(LogCategory::Hunger, "You are hungy.");
                             }
                         }
                         HungerState::Hungry => {
                             clock.state = HungerState::Starving;
                             clock.duration = 200;
                             if entity == *player_entity {
-                                log.entries.push("You are super hungy!.".to_string());
+                                log.say(LogCategory::Hunger, "You are super hungy!.");
                             }
                         }
                         HungerState::Starving => {
                             if entity == *player_entity {
-                                log.entries.push(
-                                    "You're dying. You are your own sustenance now".to_string(),
+                                log.say(
+                                    LogCategory::Hunger,
+                                    "You're dying. You are your own sustenance now",
                                 );
                             }
-                            SufferDamage::new_damage(&mut inflict_damage, entity, 1);
+                            SufferDamage::new_damage(&mut inflict_damage, entity, 1, None, None);
                         }
                     }
                 }
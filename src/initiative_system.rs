@@ -0,0 +1,84 @@
+use specs::prelude::*;
+
+use super::{status_effects, Energy, MyTurn, Position, StatusEffects};
+
+/// Energy it costs to take one action.
+pub const ACTION_COST: i32 = 100;
+
+/// Speed of the player and of anything the raws don't say otherwise about: one action per
+/// scheduler tick.
+pub const NORMAL_SPEED: i32 = 100;
+
+/// Hands out the next turns. Nothing happens while anyone on the level still has a turn to
+/// take; otherwise time passes, one scheduler tick at a time, until someone can afford to
+/// act. Everyone who can afford it gets a turn, and an entity with energy left over after
+/// acting gets another before time moves on, so double speed means two actions a tick.
+pub struct InitiativeSystem {}
+
+impl<'a> System<'a> for InitiativeSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, Energy>,
+        WriteStorage<'a, MyTurn>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, StatusEffects>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, mut energy, mut my_turn, positions, status_effects) = data;
+
+        // Entities stored on other levels have no position and stay out of the schedule
+        if (&my_turn, &positions).join().next().is_some() {
+            return;
+        }
+
+        loop {
+            let mut ready = Vec::new();
+            let mut anyone_moving = false;
+            for (entity, energy, _pos) in (&entities, &energy, &positions).join() {
+                if energy.energy >= ACTION_COST {
+                    ready.push(entity);
+                }
+                anyone_moving |= energy.speed > 0;
+            }
+            if !ready.is_empty() {
+                for entity in ready {
+                    my_turn
+                        .insert(entity, MyTurn {})
+                        .expect("Unable to insert turn");
+                }
+                return;
+            }
+            if !anyone_moving {
+                return;
+            }
+
+            for (energy, _pos, effects) in (&mut energy, &positions, status_effects.maybe()).join()
+            {
+                energy.energy += status_effects::effective_speed(energy.speed, effects).max(0);
+            }
+        }
+    }
+}
+
+/// Ends the turn of everyone who just acted: the player after a player turn, every other
+/// entity holding a turn after a monster turn. Each pays for its action out of its energy.
+pub fn end_turns(ecs: &mut World, player_turn: bool) {
+    let player_entity = *ecs.fetch::<Entity>();
+    let entities = ecs.entities();
+    let mut energy = ecs.write_storage::<Energy>();
+    let mut my_turn = ecs.write_storage::<MyTurn>();
+    let positions = ecs.read_storage::<Position>();
+
+    let acted: Vec<Entity> = (&entities, &my_turn, &positions)
+        .join()
+        .map(|(entity, _turn, _pos)| entity)
+        .filter(|entity| (*entity == player_entity) == player_turn)
+        .collect();
+    for entity in acted {
+        my_turn.remove(entity);
+        if let Some(energy) = energy.get_mut(entity) {
+            energy.energy -= ACTION_COST;
+        }
+    }
+}
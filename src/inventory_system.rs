@@ -1,7 +1,10 @@
 use super::{
-    gamelog::GameLog, particle_system::ParticleBuilder, AreaOfEffect, CombatStats, Confusion,
-    Consumable, Equippable, Equipped, HungerClock, HungerState, InBackpack, InflictsDamage, Map,
-    Name, Position, ProvidesFood, ProvidesHealing, RevealsMap, RunState, SufferDamage,
+    attributes,
+    gamelog::{GameLog, LogCategory, LogEntry},
+    particle_system::ParticleBuilder,
+    AreaOfEffect, Attributes, CombatStats, Consumable, Equippable, Equipped, HungerClock,
+    HungerState, InBackpack, InflictsDamage, InflictsStatus, Map, Name, Position, ProvidesFood,
+    ProvidesHealing, RevealsMap, RunState, Skills, StatusEffects, StatusKind, SufferDamage,
     WantsToDropItem, WantsToPickupItem, WantsToRemoveItem, WantsToUseItem,
 };
 use specs::prelude::*;
@@ -35,10 +38,12 @@ impl<'a> System<'a> for ItemCollectionSystem {
                 .expect("Unable to insert backpack entry");
 
             if pickup.collected_by == *player_entity {
-                gamelog.entries.push(format!(
-                    "You picked up {}.",
-                    names.get(pickup.item).unwrap().name
-                ));
+                gamelog.push(
+                    LogEntry::new(LogCategory::Item)
+                        .text("You picked up ")
+                        .name(&names.get(pickup.item).unwrap().name)
+                        .text("."),
+                );
             }
         }
 
@@ -65,7 +70,8 @@ impl<'a> System<'a> for ItemUseSystem {
         WriteStorage<'a, CombatStats>,
         WriteStorage<'a, SufferDamage>,
         ReadStorage<'a, AreaOfEffect>,
-        WriteStorage<'a, Confusion>,
+        ReadStorage<'a, InflictsStatus>,
+        WriteStorage<'a, StatusEffects>,
         ReadStorage<'a, RevealsMap>,
         ReadStorage<'a, Equippable>,
         WriteStorage<'a, Equipped>,
@@ -73,6 +79,8 @@ impl<'a> System<'a> for ItemUseSystem {
         WriteStorage<'a, HungerClock>,
         WriteExpect<'a, ParticleBuilder>,
         ReadStorage<'a, Position>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, Skills>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -91,7 +99,8 @@ impl<'a> System<'a> for ItemUseSystem {
             mut combat_stats,
             mut suffer_damage,
             aoe,
-            mut confused,
+            inflicts_status,
+            mut status_effects,
             reveals_map,
             equippable,
             mut equipped,
@@ -99,6 +108,8 @@ impl<'a> System<'a> for ItemUseSystem {
             mut hunger_clock,
             mut particle_builder,
             positions,
+            attributes,
+            skills,
         ) = data;
 
         for (entity, use_item) in (&entities, &wants_use).join() {
@@ -148,15 +159,33 @@ impl<'a> System<'a> for ItemUseSystem {
                 None => {}
                 Some(damage) => {
                     used_item = false;
+                    let spell_damage = attributes::spell_damage(
+                        &attributes.get(entity).cloned().unwrap_or_default(),
+                        &skills.get(entity).cloned().unwrap_or_default(),
+                    );
+                    let damage_type = damage.damage_type;
+                    let damage = i32::max(0, damage.damage + spell_damage);
                     for mob in targets.iter() {
-                        SufferDamage::new_damage(&mut suffer_damage, *mob, damage.damage);
+                        SufferDamage::new_damage(
+                            &mut suffer_damage,
+                            *mob,
+                            damage,
+                            Some(entity),
+                            Some(damage_type),
+                        );
                         if entity == *player_entity {
                             let mob_name = names.get(*mob).unwrap();
                             let item_name = names.get(use_item.item).unwrap();
-                            gamelog.entries.push(format!(
-                                "You use {} on {}, inflicting {} hp.",
-                                item_name.name, mob_name.name, damage.damage
-                            ));
+                            gamelog.push(
+                                LogEntry::new(LogCategory::Combat)
+                                    .text("You use ")
+                                    .name(&item_name.name)
+                                    .text(" on ")
+                                    .name(&mob_name.name)
+                                    .text(", inflicting ")
+                                    .damage(damage)
+                                    .text(" hp."),
+                            );
 
                             let pos = positions.get(*mob);
                             if let Some(pos) = pos {
@@ -185,11 +214,14 @@ impl<'a> System<'a> for ItemUseSystem {
                         if let Some(stats) = stats {
                             stats.hp = i32::min(stats.max_hp, stats.hp + healer.heal_amount);
                             if entity == *player_entity {
-                                gamelog.entries.push(format!(
-                                    "You use a {}, healing {} hp.",
-                                    names.get(use_item.item).unwrap().name,
-                                    healer.heal_amount
-                                ));
+                                gamelog.push(
+                                    LogEntry::new(LogCategory::Item)
+                                        .text("You use a ")
+                                        .name(&names.get(use_item.item).unwrap().name)
+                                        .text(", healing ")
+                                        .colored(healer.heal_amount, rltk::RGB::named(rltk::GREEN))
+                                        .text(" hp."),
+                                );
                             }
                             used_item = true;
 
@@ -219,10 +251,12 @@ impl<'a> System<'a> for ItemUseSystem {
                     if let Some(hc) = hc {
                         hc.state = HungerState::WellFed;
                         hc.duration = 20;
-                        gamelog.entries.push(format!(
-                            "You eat some {}.",
-                            names.get(use_item.item).unwrap().name
-                        ));
+                        gamelog.push(
+                            LogEntry::new(LogCategory::Hunger)
+                                .text("You eat some ")
+                                .name(&names.get(use_item.item).unwrap().name)
+                                .text("."),
+                        );
                     }
                 }
             }
@@ -242,7 +276,11 @@ impl<'a> System<'a> for ItemUseSystem {
                         {
                             to_unequip.push(item_entity);
                             if target == *player_entity {
-                                gamelog.entries.push(format!("You unequip {}", name.name));
+                                gamelog.push(
+                                    LogEntry::new(LogCategory::Item)
+                                        .text("You unequip ")
+                                        .name(&name.name),
+                                );
                             }
                         }
                     }
@@ -264,46 +302,57 @@ impl<'a> System<'a> for ItemUseSystem {
                         .expect("Unable to insert equipped component");
                     backpack.remove(use_item.item);
                     if target == *player_entity {
-                        gamelog.entries.push(format!(
-                            "You equip {}.",
-                            names.get(use_item.item).unwrap().name
-                        ));
+                        gamelog.push(
+                            LogEntry::new(LogCategory::Item)
+                                .text("You equip ")
+                                .name(&names.get(use_item.item).unwrap().name)
+                                .text("."),
+                        );
                     }
                 }
             }
 
-            let mut add_confusion = Vec::new();
-            {
-                let causes_confusion = confused.get(use_item.item);
-                match causes_confusion {
-                    None => {}
-                    Some(confusion) => {
-                        used_item = false;
-                        for mob in targets.iter() {
-                            add_confusion.push((*mob, confusion.turns));
-                            if entity == *player_entity {
-                                let mob_name = names.get(*mob).unwrap();
-                                let item_name = names.get(use_item.item).unwrap();
-                                gamelog.entries.push(format!(
-                                    "You use {} on {}, confusing them.",
-                                    item_name.name, mob_name.name
-                                ));
-
-                                let pos = positions.get(*mob);
-                                if let Some(pos) = pos {
-                                    particle_builder.request(
-                                        pos.x,
-                                        pos.y,
-                                        rltk::RGB::named(rltk::MAGENTA),
-                                        rltk::RGB::named(rltk::BLACK),
-                                        rltk::to_cp437('?'),
-                                        200.0,
-                                    );
-                                }
-                            }
-                            used_item = true;
+            let mut add_status = Vec::new();
+            if let Some(inflicts) = inflicts_status.get(use_item.item) {
+                let kind = inflicts.effect.kind;
+                used_item = false;
+                for mob in targets.iter().filter(|mob| combat_stats.contains(**mob)) {
+                    add_status.push((*mob, inflicts.effect));
+                    if entity == *player_entity {
+                        let item_name = names.get(use_item.item).unwrap();
+                        let entry = if *mob == *player_entity {
+                            LogEntry::new(LogCategory::Combat)
+                                .text("You use ")
+                                .name(&item_name.name)
+                                .text(", and are now ")
+                        } else {
+                            LogEntry::new(LogCategory::Combat)
+                                .text("You use ")
+                                .name(&item_name.name)
+                                .text(" on ")
+                                .name(&names.get(*mob).unwrap().name)
+                                .text(", who is now ")
+                        };
+                        gamelog.push(entry.colored(kind.name(), kind.color()).text("."));
+
+                        let pos = positions.get(*mob);
+                        if let Some(pos) = pos {
+                            let glyph = if kind == StatusKind::Confusion {
+                                '?'
+                            } else {
+                                '!'
+                            };
+                            particle_builder.request(
+                                pos.x,
+                                pos.y,
+                                kind.color(),
+                                rltk::RGB::named(rltk::BLACK),
+                                rltk::to_cp437(glyph),
+                                200.0,
+                            );
                         }
                     }
+                    used_item = true;
                 }
             }
 
@@ -312,17 +361,16 @@ impl<'a> System<'a> for ItemUseSystem {
                 None => {}
                 Some(_) => {
                     used_item = true;
-                    gamelog
-                        .entries
-                        .push("You realize you don't need eyes to see here.".to_string());
+                    gamelog.say(
+                        LogCategory::Item,
+                        "You realize you don't need eyes to see here.",
+                    );
                     *runstate = RunState::RevealingMap { row: 0 };
                 }
             }
 
-            for mob in add_confusion.iter() {
-                confused
-                    .insert(mob.0, Confusion { turns: mob.1 })
-                    .expect("Unable to insert status");
+            for (mob, effect) in add_status {
+                StatusEffects::inflict(&mut status_effects, mob, effect);
             }
 
             if used_item {
@@ -382,10 +430,12 @@ impl<'a> System<'a> for ItemDropSystem {
             backpack.remove(to_drop.item);
 
             if entity == *player_entity {
-                gamelog.entries.push(format!(
-                    "You dropped the {}.",
-                    names.get(to_drop.item).unwrap().name
-                ));
+                gamelog.push(
+                    LogEntry::new(LogCategory::Item)
+                        .text("You dropped the ")
+                        .name(&names.get(to_drop.item).unwrap().name)
+                        .text("."),
+                );
             }
         }
 
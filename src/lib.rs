@@ -0,0 +1,768 @@
+extern crate serde;
+
+use gamelog::LogCategory;
+use rltk::{GameState, Point, RandomNumberGenerator, Rltk};
+use specs::prelude::*;
+use specs::saveload::SimpleMarkerAllocator;
+
+pub mod attributes;
+pub mod camera;
+pub mod combat;
+#[macro_use]
+mod components;
+pub mod dungeon;
+pub mod experience;
+pub use components::*;
+mod map;
+pub use map::*;
+pub mod map_builders;
+mod player;
+use player::*;
+mod rect;
+pub use rect::Rect;
+mod visibility_system;
+use visibility_system::VisibilitySystem;
+mod monster_ai_system;
+use monster_ai_system::MonsterAI;
+mod map_indexing_system;
+use map_indexing_system::MapIndexingSystem;
+mod melee_combat_system;
+use melee_combat_system::MeleeCombatSystem;
+mod damage_system;
+use damage_system::DamageSystem;
+pub mod game_clock;
+pub mod gamelog;
+mod gui;
+pub mod headless;
+mod hunger_system;
+pub mod initiative_system;
+mod inventory_system;
+mod particle_system;
+mod spawner;
+mod trigger_system;
+use inventory_system::{ItemCollectionSystem, ItemDropSystem, ItemRemoveSystem, ItemUseSystem};
+pub mod random_table;
+pub mod raws;
+mod rex_assets;
+pub mod saveload_system;
+pub mod status_effects;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum RunState {
+    AwaitingInput,
+    PreRun,
+    Ticking,
+    PlayerTurn,
+    MonsterTurn,
+    ShowInventory,
+    ShowDropItem,
+    ShowRemoveItem,
+    ShowTargeting {
+        range: i32,
+        item: Entity,
+    },
+    RevealingMap {
+        row: i32,
+    },
+    MainMenu {
+        menu_selection: gui::MainMenuSelection,
+    },
+    SaveGame,
+    LevelUp,
+    CharacterSheet,
+    LogHistory {
+        offset: usize,
+    },
+    CrashRecovery {
+        slot: usize,
+    },
+    SlotMenu {
+        mode: gui::SlotMenuMode,
+        selection: usize,
+    },
+    NextLevel,
+    PreviousLevel,
+    GameOver,
+}
+
+/// The seed the current run's `RandomNumberGenerator` was created from. Every roll in a run
+/// comes from that one generator, so the seed is enough to reproduce it.
+pub struct RunSeed {
+    pub seed: u64,
+}
+
+pub struct State {
+    pub ecs: World,
+    systems: Dispatcher<'static, 'static>,
+}
+
+/// The systems that run every turn. The game log and the random number generator are used
+/// in the same order every turn so that a seed reproduces its run, which chains most of
+/// them; the rest run side by side where their data doesn't overlap: map indexing with
+/// melee combat, and item removal, hunger and particle spawning after items are dropped.
+/// Damage runs after every system that deals it. Before turns were scheduled by energy it
+/// ran straight after melee combat, and damage from items and hunger waited for the next
+/// turn's pass; now it lands on the turn it was dealt.
+pub fn turn_systems() -> DispatcherBuilder<'static, 'static> {
+    build_turn_systems(false)
+}
+
+/// The same systems with each one waiting for the one before, to measure `turn_systems`
+/// against.
+pub fn chained_turn_systems() -> DispatcherBuilder<'static, 'static> {
+    build_turn_systems(true)
+}
+
+fn build_turn_systems(chained: bool) -> DispatcherBuilder<'static, 'static> {
+    TurnSystems {
+        builder: DispatcherBuilder::new(),
+        chained,
+    }
+    .with(VisibilitySystem {}, "visibility", &[])
+    .with(MonsterAI {}, "monster_ai", &["visibility"])
+    .with(
+        trigger_system::TriggerSystem {},
+        "triggers",
+        &["monster_ai"],
+    )
+    .with(MapIndexingSystem {}, "map_indexing", &["triggers"])
+    .with(MeleeCombatSystem {}, "melee_combat", &["triggers"])
+    // Before item use, which can leave the run state on something other than the turn
+    .with(
+        status_effects::StatusEffectSystem {},
+        "status_effects",
+        &["melee_combat"],
+    )
+    .with(
+        ItemCollectionSystem {},
+        "pickup",
+        &["map_indexing", "status_effects"],
+    )
+    .with(ItemUseSystem {}, "item_use", &["pickup"])
+    .with(ItemDropSystem {}, "drop_items", &["item_use"])
+    .with(ItemRemoveSystem {}, "remove_items", &["drop_items"])
+    .with(hunger_system::HungerSystem {}, "hunger", &["drop_items"])
+    .with(
+        particle_system::ParticleSpawnSystem {},
+        "particles",
+        &["drop_items"],
+    )
+    // After particles too, which write the positions it reads
+    .with(DamageSystem {}, "damage", &["hunger", "particles"])
+    .builder
+}
+
+/// A `DispatcherBuilder` that can put a barrier before every system it adds.
+struct TurnSystems {
+    builder: DispatcherBuilder<'static, 'static>,
+    chained: bool,
+}
+
+impl TurnSystems {
+    fn with<S>(mut self, system: S, name: &str, deps: &[&str]) -> Self
+    where
+        S: for<'c> System<'c> + Send + 'static,
+    {
+        if self.chained {
+            self.builder.add_barrier();
+        }
+        self.builder.add(system, name, deps);
+        self
+    }
+}
+
+impl State {
+    pub fn new(seed: u64) -> State {
+        let mut gs = State {
+            ecs: World::new(),
+            systems: turn_systems().build(),
+        };
+        register_components(&mut gs.ecs);
+
+        gs.ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+
+        gs.ecs.insert(particle_system::ParticleBuilder::new());
+
+        gs.ecs.insert(rex_assets::RexAssets::new());
+
+        gs.ecs.insert(raws::RawMaster::load());
+
+        gs.ecs.insert(RunSeed { seed });
+        gs.ecs.insert(RandomNumberGenerator::seeded(seed));
+        gs.ecs.insert(game_clock::GameClock::default());
+        gs.ecs.insert(saveload_system::SaveSlots::in_data_dir());
+
+        gs.ecs.insert(Map::default());
+        gs.ecs.insert(dungeon::MasterDungeonMap::default());
+        let start = gs.generate_world_map(1);
+
+        let player_entity = spawner::player(&mut gs.ecs, start.x, start.y);
+
+        gs.ecs.insert(Point::new(start.x, start.y));
+        gs.ecs.insert(player_entity);
+        gs.ecs.insert(RunState::MainMenu {
+            menu_selection: gui::MainMenuSelection::NewGame,
+        });
+        let mut log = gamelog::GameLog::new();
+        log.say(LogCategory::System, "Welcome to this Rusted Roguelike");
+        gs.ecs.insert(log);
+
+        gs
+    }
+
+    /// Builds the level for `new_depth`, makes it the current map and populates it.
+    /// Returns where the player should start.
+    fn generate_world_map(&mut self, new_depth: i32) -> Position {
+        let builder = {
+            let mut rng = self.ecs.write_resource::<RandomNumberGenerator>();
+            loop {
+                let mut builder = map_builders::builder_for_depth(new_depth, &mut rng);
+                builder.build_map(&mut rng);
+                // A start walled in on its own is also the most distant tile, so the down
+                // stairs go there, and the up stairs would cover them; build another level
+                let start = builder.get_starting_position();
+                let map = builder.get_map();
+                if map.tiles[map.xy_idx(start.x, start.y)] != TileType::DownStairs {
+                    break builder;
+                }
+            }
+        };
+
+        let mut map = builder.get_map();
+        let start = builder.get_starting_position();
+        if new_depth > 1 {
+            let start_idx = map.xy_idx(start.x, start.y);
+            map.tiles[start_idx] = TileType::UpStairs;
+        }
+        *self.ecs.write_resource::<Map>() = map;
+        for region in builder.get_spawn_regions().iter() {
+            spawner::spawn_region(&mut self.ecs, region, new_depth);
+        }
+        let width = self.ecs.fetch::<Map>().width;
+        for (idx, name) in builder.get_spawn_list().iter() {
+            let x = *idx as i32 % width;
+            let y = *idx as i32 / width;
+            if raws::spawn_named(&mut self.ecs, name, x, y).is_none() {
+                rltk::console::log(format!("WARNING: prefab spawns unknown entity {}", name));
+            }
+        }
+
+        start
+    }
+
+    fn run_system(&mut self) {
+        self.ecs.write_resource::<game_clock::GameClock>().tick += 1;
+        self.systems.dispatch(&self.ecs);
+        self.ecs.maintain();
+    }
+
+    /// Steps the run states that need neither input nor a console, so the windowed game and
+    /// the headless runner move through the turn schedule the same way.
+    pub fn advance(&mut self, runstate: RunState) -> RunState {
+        match runstate {
+            RunState::PreRun => {
+                self.run_system();
+                RunState::Ticking
+            }
+            RunState::Ticking => {
+                let mut initiative = initiative_system::InitiativeSystem {};
+                initiative.run_now(&self.ecs);
+                let player_entity = *self.ecs.fetch::<Entity>();
+                let player_ready = self.ecs.read_storage::<MyTurn>().contains(player_entity);
+                let monsters_ready = (
+                    &self.ecs.read_storage::<MyTurn>(),
+                    &self.ecs.read_storage::<Position>(),
+                )
+                    .join()
+                    .next()
+                    .is_some();
+                if player_ready || !monsters_ready {
+                    // The player goes first among everyone ready on the same tick
+                    let turn = self.ecs.fetch::<game_clock::GameClock>().turn;
+                    if turn > 0 && turn.is_multiple_of(saveload_system::AUTOSAVE_INTERVAL) {
+                        self.autosave();
+                    }
+                    if player_ready && status_effects::is_paralyzed(&self.ecs, player_entity) {
+                        // The turn passes without the player getting a say
+                        RunState::PlayerTurn
+                    } else {
+                        self.input_state()
+                    }
+                } else {
+                    RunState::MonsterTurn
+                }
+            }
+            RunState::PlayerTurn => {
+                self.ecs.write_resource::<game_clock::GameClock>().turn += 1;
+                self.run_system();
+                initiative_system::end_turns(&mut self.ecs, true);
+                match *self.ecs.fetch::<RunState>() {
+                    RunState::RevealingMap { .. } => RunState::RevealingMap { row: 0 },
+                    _ => RunState::Ticking,
+                }
+            }
+            RunState::MonsterTurn => {
+                self.run_system();
+                initiative_system::end_turns(&mut self.ecs, false);
+                RunState::Ticking
+            }
+            RunState::RevealingMap { row } => {
+                let mut map = self.ecs.fetch_mut::<Map>();
+                for x in 0..map.width {
+                    let idx = map.xy_idx(x, row);
+                    map.revealed_tiles[idx] = true;
+                }
+                if row == map.height - 1 {
+                    RunState::Ticking
+                } else {
+                    RunState::RevealingMap { row: row + 1 }
+                }
+            }
+            RunState::NextLevel => {
+                self.goto_level(1);
+                self.autosave();
+                RunState::PreRun
+            }
+            RunState::PreviousLevel => {
+                self.goto_level(-1);
+                self.autosave();
+                RunState::PreRun
+            }
+            _ => runstate,
+        }
+    }
+}
+
+impl GameState for State {
+    fn tick(&mut self, ctx: &mut Rltk) {
+        let mut newrunstate;
+        {
+            let runstate = self.ecs.fetch::<RunState>();
+            newrunstate = *runstate;
+        }
+
+        ctx.cls();
+        particle_system::cull_dead_particle(&mut self.ecs, ctx);
+
+        match newrunstate {
+            RunState::MainMenu { .. }
+            | RunState::LogHistory { .. }
+            | RunState::CrashRecovery { .. }
+            | RunState::SlotMenu {
+                mode: gui::SlotMenuMode::Load,
+                ..
+            } => {}
+            _ => {
+                camera::render_camera(&self.ecs, ctx);
+                gui::draw_ui(&self.ecs, ctx);
+            }
+        }
+
+        match newrunstate {
+            RunState::PreRun
+            | RunState::Ticking
+            | RunState::PlayerTurn
+            | RunState::MonsterTurn
+            | RunState::RevealingMap { .. }
+            | RunState::NextLevel
+            | RunState::PreviousLevel => newrunstate = self.advance(newrunstate),
+            RunState::AwaitingInput => newrunstate = player_input(self, ctx),
+            RunState::ShowInventory => {
+                let result = gui::show_inventory(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        let is_ranged = self.ecs.read_storage::<Ranged>();
+                        let is_item_ranged = is_ranged.get(item_entity);
+                        if let Some(is_item_ranged) = is_item_ranged {
+                            newrunstate = RunState::ShowTargeting {
+                                range: is_item_ranged.range,
+                                item: item_entity,
+                            };
+                        } else {
+                            let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                            intent
+                                .insert(
+                                    *self.ecs.fetch::<Entity>(),
+                                    WantsToUseItem {
+                                        item: item_entity,
+                                        target: None,
+                                    },
+                                )
+                                .expect("Unable to insert intent");
+                            newrunstate = RunState::PlayerTurn;
+                        }
+                    }
+                }
+            }
+            RunState::ShowDropItem => {
+                let result = gui::drop_item_menu(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        let mut intent = self.ecs.write_storage::<WantsToDropItem>();
+                        intent
+                            .insert(
+                                *self.ecs.fetch::<Entity>(),
+                                WantsToDropItem { item: item_entity },
+                            )
+                            .expect("Unable to insert intent");
+                        newrunstate = RunState::PlayerTurn;
+                    }
+                }
+            }
+
+            RunState::ShowRemoveItem => {
+                let result = gui::remove_item_menu(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        let mut intent = self.ecs.write_storage::<WantsToRemoveItem>();
+                        intent
+                            .insert(
+                                *self.ecs.fetch::<Entity>(),
+                                WantsToRemoveItem { item: item_entity },
+                            )
+                            .expect("Unable to insert intent");
+                        newrunstate = RunState::PlayerTurn;
+                    }
+                }
+            }
+
+            RunState::ShowTargeting { range, item } => {
+                let result = gui::ranged_target(self, ctx, range);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                        intent
+                            .insert(
+                                *self.ecs.fetch::<Entity>(),
+                                WantsToUseItem {
+                                    item,
+                                    target: result.1,
+                                },
+                            )
+                            .expect("Unable to insert intent");
+                        newrunstate = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::LevelUp => {
+                if let gui::LevelUpResult::Selected(choice) = gui::level_up_menu(self, ctx) {
+                    newrunstate = self.choose_level_up(choice);
+                }
+            }
+            RunState::CharacterSheet => {
+                if gui::character_sheet(self, ctx) == gui::CharacterSheetResult::Close {
+                    newrunstate = RunState::AwaitingInput;
+                }
+            }
+            RunState::LogHistory { .. } => match gui::log_history(self, ctx) {
+                gui::LogHistoryResult::NoResponse { offset } => {
+                    newrunstate = RunState::LogHistory { offset }
+                }
+                gui::LogHistoryResult::Close => newrunstate = RunState::AwaitingInput,
+            },
+            RunState::MainMenu { .. } => {
+                let result = gui::main_menu(self, ctx);
+                match result {
+                    gui::MainMenuResult::NoSelection { selected } => {
+                        newrunstate = RunState::MainMenu {
+                            menu_selection: selected,
+                        }
+                    }
+                    gui::MainMenuResult::Selected { selected } => match selected {
+                        gui::MainMenuSelection::NewGame => {
+                            self.ecs.remove::<saveload_system::LoadFailure>();
+                            newrunstate = RunState::PreRun;
+                        }
+                        gui::MainMenuSelection::LoadGame => {
+                            newrunstate = self.open_slot_menu(gui::SlotMenuMode::Load);
+                        }
+                        gui::MainMenuSelection::Quit => {
+                            ::std::process::exit(0);
+                        }
+                    },
+                }
+            }
+
+            RunState::CrashRecovery { slot } => match gui::crash_recovery(self, ctx) {
+                gui::CrashRecoveryResult::NoResponse => {}
+                gui::CrashRecoveryResult::Recover => newrunstate = self.load_from_slot(slot),
+                gui::CrashRecoveryResult::Later => {
+                    newrunstate = RunState::MainMenu {
+                        menu_selection: gui::MainMenuSelection::LoadGame,
+                    }
+                }
+            },
+
+            RunState::SaveGame => newrunstate = self.open_slot_menu(gui::SlotMenuMode::Save),
+
+            RunState::SlotMenu { mode, .. } => match gui::slot_menu(self, ctx) {
+                gui::SlotMenuResult::NoSelection { selected } => {
+                    newrunstate = RunState::SlotMenu {
+                        mode,
+                        selection: selected,
+                    }
+                }
+                gui::SlotMenuResult::ToggleFormat => {
+                    let mut slots = self.ecs.write_resource::<saveload_system::SaveSlots>();
+                    slots.format = match slots.format {
+                        saveload_system::SaveFormat::Binary => saveload_system::SaveFormat::Json,
+                        saveload_system::SaveFormat::Json => saveload_system::SaveFormat::Binary,
+                    };
+                }
+                gui::SlotMenuResult::Cancel => {
+                    newrunstate = match mode {
+                        gui::SlotMenuMode::Save => RunState::AwaitingInput,
+                        gui::SlotMenuMode::Load => RunState::MainMenu {
+                            menu_selection: gui::MainMenuSelection::LoadGame,
+                        },
+                    }
+                }
+                gui::SlotMenuResult::Selected { selected } => {
+                    newrunstate = match mode {
+                        gui::SlotMenuMode::Save => self.save_to_slot(selected),
+                        gui::SlotMenuMode::Load => self.load_from_slot(selected),
+                    }
+                }
+            },
+
+            RunState::GameOver => {
+                let result = gui::game_over(ctx);
+                match result {
+                    gui::GameOverResult::NoSelection => {}
+                    gui::GameOverResult::QuitToMenu => {
+                        self.game_over_cleanup();
+                        newrunstate = RunState::MainMenu {
+                            menu_selection: gui::MainMenuSelection::NewGame,
+                        };
+                    }
+                }
+            }
+        }
+
+        {
+            let mut runwriter = self.ecs.write_resource::<RunState>();
+            *runwriter = newrunstate;
+        }
+        damage_system::delete_the_dead(&mut self.ecs);
+    }
+}
+
+impl State {
+    /// Leaves the current level for the one `offset` levels below it (negative goes up).
+    /// The level being left is kept, entities and all, for when the player comes back.
+    fn goto_level(&mut self, offset: i32) {
+        dungeon::freeze_level_entities(&mut self.ecs);
+        self.ecs.maintain();
+
+        let current_depth = self.ecs.fetch::<Map>().depth;
+        self.ecs
+            .write_resource::<dungeon::MasterDungeonMap>()
+            .store_map(&self.ecs.fetch::<Map>());
+
+        let new_depth = current_depth + offset;
+        let stored = self
+            .ecs
+            .fetch::<dungeon::MasterDungeonMap>()
+            .get_map(new_depth);
+        let first_visit = stored.is_none();
+        let (player_x, player_y) = match stored {
+            Some(map) => {
+                let arrival = dungeon::arrival_point(&map, offset > 0)
+                    .expect("Visited levels have stairs both ways");
+                *self.ecs.write_resource::<Map>() = map;
+                dungeon::thaw_level_entities(&mut self.ecs, new_depth);
+                arrival
+            }
+            None => {
+                let start = self.generate_world_map(new_depth);
+                (start.x, start.y)
+            }
+        };
+
+        let player_entity = *self.ecs.fetch::<Entity>();
+        *self.ecs.write_resource::<Point>() = Point::new(player_x, player_y);
+        if let Some(pos) = self.ecs.write_storage::<Position>().get_mut(player_entity) {
+            pos.x = player_x;
+            pos.y = player_y;
+        }
+        if let Some(vs) = self.ecs.write_storage::<Viewshed>().get_mut(player_entity) {
+            vs.dirty = true;
+        }
+
+        let mut gamelog = self.ecs.fetch_mut::<gamelog::GameLog>();
+        if !first_visit {
+            let direction = if offset > 0 { "down" } else { "up" };
+            gamelog.say(
+                LogCategory::System,
+                format!("You head back {} the stairs.", direction),
+            );
+            return;
+        }
+
+        gamelog.say(
+            LogCategory::System,
+            "You fall down to the next level, and take some time to heal your booboo.",
+        );
+        let mut player_health_store = self.ecs.write_storage::<CombatStats>();
+        if let Some(player_health) = player_health_store.get_mut(player_entity) {
+            player_health.hp = i32::max(player_health.hp, player_health.max_hp / 2);
+        }
+    }
+
+    /// Where the player's turn starts: picking what a new level improves, if they've earned
+    /// one, otherwise the usual input.
+    fn input_state(&self) -> RunState {
+        if experience::level_up_pending(&self.ecs) {
+            RunState::LevelUp
+        } else {
+            RunState::AwaitingInput
+        }
+    }
+
+    pub fn choose_level_up(&mut self, choice: experience::LevelUpChoice) -> RunState {
+        experience::level_up(&mut self.ecs, choice);
+        self.input_state()
+    }
+
+    /// Starts on the crash recovery prompt instead of the main menu if a run was left
+    /// autosaved but never saved and quit.
+    pub fn offer_crash_recovery(&mut self) {
+        let slots = (*self.ecs.fetch::<saveload_system::SaveSlots>()).clone();
+        if let Some((slot, _)) = slots.find_autosave() {
+            self.ecs.insert(gui::SlotListing {
+                slots: slots.list(),
+            });
+            self.ecs.insert(RunState::CrashRecovery { slot });
+        }
+    }
+
+    /// Saves the run into its slot without stopping play, so a crash doesn't lose it.
+    fn autosave(&mut self) {
+        let slot = {
+            let slots = self.ecs.fetch::<saveload_system::SaveSlots>();
+            if !slots.autosave {
+                return;
+            }
+            slots.autosave_slot()
+        };
+        let result = match slot {
+            None => Err("every save slot is in use".to_string()),
+            Some(slot) => {
+                saveload_system::save_game(&mut self.ecs, slot, saveload_system::SaveKind::Autosave)
+                    .map_err(|err| err.to_string())
+            }
+        };
+        if let Err(err) = result {
+            let mut log = self.ecs.write_resource::<gamelog::GameLog>();
+            log.say(LogCategory::System, format!("Autosave failed: {}", err));
+        }
+    }
+
+    fn open_slot_menu(&mut self, mode: gui::SlotMenuMode) -> RunState {
+        let slots = (*self.ecs.fetch::<saveload_system::SaveSlots>()).clone();
+        let selection = match mode {
+            gui::SlotMenuMode::Save => slots.current.or_else(|| slots.first_free()),
+            gui::SlotMenuMode::Load => {
+                (0..saveload_system::SAVE_SLOT_COUNT).find(|slot| slots.is_used(*slot))
+            }
+        };
+        self.ecs.insert(gui::SlotListing {
+            slots: slots.list(),
+        });
+        RunState::SlotMenu {
+            mode,
+            selection: selection.unwrap_or(0),
+        }
+    }
+
+    fn save_to_slot(&mut self, slot: usize) -> RunState {
+        match saveload_system::save_game(&mut self.ecs, slot, saveload_system::SaveKind::Manual) {
+            Ok(()) => RunState::MainMenu {
+                menu_selection: gui::MainMenuSelection::Quit,
+            },
+            Err(err) => {
+                let mut log = self.ecs.write_resource::<gamelog::GameLog>();
+                log.say(
+                    LogCategory::System,
+                    format!("Couldn't save the game: {}", err),
+                );
+                RunState::AwaitingInput
+            }
+        }
+    }
+
+    fn load_from_slot(&mut self, slot: usize) -> RunState {
+        match saveload_system::load_game(&mut self.ecs, slot) {
+            Ok(()) => {
+                self.ecs.remove::<saveload_system::LoadFailure>();
+                RunState::AwaitingInput
+            }
+            Err(err) => {
+                // The failed load may have cleared the world already
+                self.game_over_cleanup();
+                self.ecs.insert(saveload_system::LoadFailure {
+                    message: format!("Couldn't load: {}", err),
+                });
+                RunState::MainMenu {
+                    menu_selection: gui::MainMenuSelection::NewGame,
+                }
+            }
+        }
+    }
+
+    fn game_over_cleanup(&mut self) {
+        // Delete everything
+        let mut to_delete = Vec::new();
+        for e in self.ecs.entities().join() {
+            to_delete.push(e);
+        }
+        for del in to_delete.iter() {
+            self.ecs.delete_entity(*del).expect("Deletion failed");
+        }
+
+        // A new run gets a fresh seed
+        let seed = RandomNumberGenerator::new().next_u64();
+        self.ecs.insert(RunSeed { seed });
+        self.ecs.insert(RandomNumberGenerator::seeded(seed));
+        self.ecs.insert(game_clock::GameClock::default());
+        self.ecs
+            .write_resource::<saveload_system::SaveSlots>()
+            .current = None;
+
+        // Build a new map and spawn bad guys
+        self.ecs.insert(dungeon::MasterDungeonMap::default());
+        let start = self.generate_world_map(1);
+
+        // Place the player and update resources
+        let (player_x, player_y) = (start.x, start.y);
+        let player_entity = spawner::player(&mut self.ecs, player_x, player_y);
+        let mut player_position = self.ecs.write_resource::<Point>();
+        *player_position = Point::new(player_x, player_y);
+        let mut position_components = self.ecs.write_storage::<Position>();
+        let mut player_entity_writer = self.ecs.write_resource::<Entity>();
+        *player_entity_writer = player_entity;
+        let player_pos_comp = position_components.get_mut(player_entity);
+        if let Some(player_pos_comp) = player_pos_comp {
+            player_pos_comp.x = player_x;
+            player_pos_comp.y = player_y;
+        }
+
+        // Mark the player's visibility as dirty
+        let mut viewshed_components = self.ecs.write_storage::<Viewshed>();
+        let vs = viewshed_components.get_mut(player_entity);
+        if let Some(vs) = vs {
+            vs.dirty = true;
+        }
+    }
+}
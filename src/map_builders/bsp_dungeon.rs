@@ -0,0 +1,177 @@
+use super::{
+    common::{apply_room_to_map, central_room, room_spawn_regions, stairs_position},
+    MapBuilder,
+};
+use crate::{Map, Position, Rect, TileType};
+use rltk::RandomNumberGenerator;
+
+/// Binary space partitioning: the map is split into ever smaller rectangles and a room is
+/// placed in some of them, so rooms pack tightly without overlapping.
+pub struct BspDungeonBuilder {
+    map: Map,
+    starting_position: Position,
+    rects: Vec<Rect>,
+}
+
+impl BspDungeonBuilder {
+    pub fn new(new_depth: i32, width: i32, height: i32) -> BspDungeonBuilder {
+        BspDungeonBuilder {
+            map: Map::new(new_depth, width, height),
+            starting_position: Position { x: 0, y: 0 },
+            rects: Vec::new(),
+        }
+    }
+
+    fn add_subrects(&mut self, rect: Rect) {
+        let width = i32::abs(rect.x1 - rect.x2);
+        let height = i32::abs(rect.y1 - rect.y2);
+        let half_width = i32::max(width / 2, 1);
+        let half_height = i32::max(height / 2, 1);
+
+        self.rects
+            .push(Rect::new(rect.x1, rect.y1, half_width, half_height));
+        self.rects.push(Rect::new(
+            rect.x1,
+            rect.y1 + half_height,
+            half_width,
+            half_height,
+        ));
+        self.rects.push(Rect::new(
+            rect.x1 + half_width,
+            rect.y1,
+            half_width,
+            half_height,
+        ));
+        self.rects.push(Rect::new(
+            rect.x1 + half_width,
+            rect.y1 + half_height,
+            half_width,
+            half_height,
+        ));
+    }
+
+    fn get_random_sub_rect(rect: Rect, rng: &mut RandomNumberGenerator) -> Rect {
+        let rect_width = i32::abs(rect.x1 - rect.x2);
+        let rect_height = i32::abs(rect.y1 - rect.y2);
+
+        let w = i32::max(3, rng.roll_dice(1, i32::min(rect_width, 10)) - 1) + 1;
+        let h = i32::max(3, rng.roll_dice(1, i32::min(rect_height, 10)) - 1) + 1;
+        let x = rect.x1 + rng.roll_dice(1, 6) - 1;
+        let y = rect.y1 + rng.roll_dice(1, 6) - 1;
+
+        Rect::new(x, y, w, h)
+    }
+
+    /// A room fits if it and a two tile margin around it are inside the map and still
+    /// solid wall.
+    fn is_possible(&self, rect: Rect) -> bool {
+        let expanded = Rect {
+            x1: rect.x1 - 2,
+            x2: rect.x2 + 2,
+            y1: rect.y1 - 2,
+            y2: rect.y2 + 2,
+        };
+
+        for y in expanded.y1..=expanded.y2 {
+            for x in expanded.x1..=expanded.x2 {
+                if x < 1 || x > self.map.width - 2 || y < 1 || y > self.map.height - 2 {
+                    return false;
+                }
+                let idx = self.map.xy_idx(x, y);
+                if self.map.tiles[idx] != TileType::Wall {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Digs a corridor that steps towards the target one axis at a time.
+    fn draw_corridor(&mut self, x1: i32, y1: i32, x2: i32, y2: i32) {
+        let mut x = x1;
+        let mut y = y1;
+
+        while x != x2 || y != y2 {
+            if x < x2 {
+                x += 1;
+            } else if x > x2 {
+                x -= 1;
+            } else if y < y2 {
+                y += 1;
+            } else if y > y2 {
+                y -= 1;
+            }
+
+            let idx = self.map.xy_idx(x, y);
+            self.map.tiles[idx] = TileType::Floor;
+        }
+    }
+}
+
+impl MapBuilder for BspDungeonBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        const MAX_ATTEMPTS: i32 = 240;
+
+        self.rects.clear();
+        let first_room = Rect::new(2, 2, self.map.width - 5, self.map.height - 5);
+        self.rects.push(first_room);
+        self.add_subrects(first_room);
+
+        for _ in 0..MAX_ATTEMPTS {
+            let rect = *rng
+                .random_slice_entry(&self.rects)
+                .expect("There is always a rect to split");
+            let candidate = BspDungeonBuilder::get_random_sub_rect(rect, rng);
+
+            if self.is_possible(candidate) {
+                apply_room_to_map(&mut self.map, &candidate);
+                self.map.rooms.push(candidate);
+                self.add_subrects(rect);
+            }
+        }
+
+        // Too small a map for any room to fit still gets one
+        if self.map.rooms.is_empty() {
+            let room = central_room(&self.map);
+            apply_room_to_map(&mut self.map, &room);
+            self.map.rooms.push(room);
+        }
+
+        let mut rooms = self.map.rooms.clone();
+        rooms.sort_by_key(|room| room.x1);
+        self.map.rooms = rooms.clone();
+
+        for pair in rooms.windows(2) {
+            let (room, next_room) = (pair[0], pair[1]);
+            let start_x = room.x1 + 1 + (rng.roll_dice(1, i32::abs(room.x1 - room.x2)) - 1);
+            let start_y = room.y1 + 1 + (rng.roll_dice(1, i32::abs(room.y1 - room.y2)) - 1);
+            let end_x =
+                next_room.x1 + 1 + (rng.roll_dice(1, i32::abs(next_room.x1 - next_room.x2)) - 1);
+            let end_y =
+                next_room.y1 + 1 + (rng.roll_dice(1, i32::abs(next_room.y1 - next_room.y2)) - 1);
+            self.draw_corridor(start_x, start_y, end_x, end_y);
+        }
+
+        let stairs = stairs_position(&self.map.rooms);
+        let stairs_idx = self.map.xy_idx(stairs.0, stairs.1);
+        self.map.tiles[stairs_idx] = TileType::DownStairs;
+
+        let (start_x, start_y) = self.map.rooms[0].center();
+        self.starting_position = Position {
+            x: start_x,
+            y: start_y,
+        };
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_regions(&self) -> Vec<Vec<usize>> {
+        room_spawn_regions(&self.map, &self.map.rooms)
+    }
+}
@@ -0,0 +1,116 @@
+use super::{
+    common::{chunk_spawn_regions, remove_unreachable_areas_returning_most_distant},
+    MapBuilder,
+};
+use crate::{Map, Position, TileType};
+use rltk::RandomNumberGenerator;
+
+/// Natural looking caves: random noise smoothed by repeatedly letting each tile follow the
+/// majority of its neighbours.
+pub struct CellularAutomataBuilder {
+    map: Map,
+    starting_position: Position,
+}
+
+impl CellularAutomataBuilder {
+    pub fn new(new_depth: i32, width: i32, height: i32) -> CellularAutomataBuilder {
+        CellularAutomataBuilder {
+            map: Map::new(new_depth, width, height),
+            starting_position: Position { x: 0, y: 0 },
+        }
+    }
+}
+
+impl MapBuilder for CellularAutomataBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        const ITERATIONS: i32 = 15;
+
+        let width = self.map.width;
+        let height = self.map.height;
+
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                let idx = self.map.xy_idx(x, y);
+                self.map.tiles[idx] = if rng.roll_dice(1, 100) > 55 {
+                    TileType::Floor
+                } else {
+                    TileType::Wall
+                };
+            }
+        }
+
+        for _ in 0..ITERATIONS {
+            let mut newtiles = self.map.tiles.clone();
+
+            for y in 1..height - 1 {
+                for x in 1..width - 1 {
+                    let mut neighbors = 0;
+                    for (dx, dy) in &[
+                        (-1, -1),
+                        (0, -1),
+                        (1, -1),
+                        (-1, 0),
+                        (1, 0),
+                        (-1, 1),
+                        (0, 1),
+                        (1, 1),
+                    ] {
+                        if self.map.tiles[self.map.xy_idx(x + dx, y + dy)] == TileType::Wall {
+                            neighbors += 1;
+                        }
+                    }
+
+                    let idx = self.map.xy_idx(x, y);
+                    newtiles[idx] = if neighbors > 4 || neighbors == 0 {
+                        TileType::Wall
+                    } else {
+                        TileType::Floor
+                    };
+                }
+            }
+
+            self.map.tiles = newtiles;
+        }
+
+        // Start in the middle, sliding left until we stand on open ground
+        self.starting_position = Position {
+            x: width / 2,
+            y: height / 2,
+        };
+        let mut start_idx = self
+            .map
+            .xy_idx(self.starting_position.x, self.starting_position.y);
+        while self.map.tiles[start_idx] != TileType::Floor {
+            self.starting_position.x -= 1;
+            if self.starting_position.x < 1 {
+                // A whole row of wall; dig the start out instead
+                self.starting_position.x = width / 2;
+                start_idx = self
+                    .map
+                    .xy_idx(self.starting_position.x, self.starting_position.y);
+                self.map.tiles[start_idx] = TileType::Floor;
+            }
+            start_idx = self
+                .map
+                .xy_idx(self.starting_position.x, self.starting_position.y);
+        }
+
+        let exit_idx = remove_unreachable_areas_returning_most_distant(&mut self.map, start_idx);
+        self.map.tiles[exit_idx] = TileType::DownStairs;
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_regions(&self) -> Vec<Vec<usize>> {
+        let start_idx = self
+            .map
+            .xy_idx(self.starting_position.x, self.starting_position.y);
+        chunk_spawn_regions(&self.map, start_idx)
+    }
+}
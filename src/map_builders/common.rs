@@ -0,0 +1,145 @@
+use super::super::{Map, Rect, TileType};
+use std::cmp::{max, min};
+
+/// Size of the square chunks open-plan maps are cut into for spawning.
+const REGION_SIZE: i32 = 16;
+
+pub fn apply_room_to_map(map: &mut Map, room: &Rect) {
+    for y in room.y1 + 1..=room.y2 {
+        for x in room.x1 + 1..=room.x2 {
+            let idx = map.xy_idx(x, y);
+            map.tiles[idx] = TileType::Floor;
+        }
+    }
+}
+
+/// A room in the middle of the map, for when a builder couldn't fit any rooms of its own.
+pub fn central_room(map: &Map) -> Rect {
+    let w = min(map.width - 3, 10);
+    let h = min(map.height - 3, 10);
+    Rect::new((map.width - w) / 2 - 1, (map.height - h) / 2 - 1, w, h)
+}
+
+/// Where the down stairs go: the middle of the last room, or its far corner when that's also
+/// the first room, whose middle is where the player starts.
+pub fn stairs_position(rooms: &[Rect]) -> (i32, i32) {
+    let last = rooms.last().expect("There is always a room");
+    if rooms.len() == 1 {
+        (last.x2, last.y2)
+    } else {
+        last.center()
+    }
+}
+
+pub fn apply_horizontal_tunnel(map: &mut Map, x1: i32, x2: i32, y: i32) {
+    for x in min(x1, x2)..=max(x1, x2) {
+        let idx = map.xy_idx(x, y);
+        if idx > 0 && idx < map.width as usize * map.height as usize {
+            map.tiles[idx] = TileType::Floor;
+        }
+    }
+}
+
+pub fn apply_vertical_tunnel(map: &mut Map, y1: i32, y2: i32, x: i32) {
+    for y in min(y1, y2)..=max(y1, y2) {
+        let idx = map.xy_idx(x, y);
+        if idx > 0 && idx < map.width as usize * map.height as usize {
+            map.tiles[idx] = TileType::Floor;
+        }
+    }
+}
+
+/// Walls off every floor tile the player can't walk to from `start_idx` and returns the
+/// reachable tile furthest away from it, which is where the stairs go.
+pub fn remove_unreachable_areas_returning_most_distant(map: &mut Map, start_idx: usize) -> usize {
+    map.populate_blocked();
+    let dijkstra_map = rltk::DijkstraMap::new(map.width, map.height, &[start_idx], map, 10_000.0);
+
+    let mut exit_tile = (start_idx, 0.0f32);
+    for (i, tile) in map.tiles.iter_mut().enumerate() {
+        if *tile == TileType::Floor {
+            let distance = dijkstra_map.map[i];
+            if distance == f32::MAX {
+                *tile = TileType::Wall;
+            } else if distance > exit_tile.1 {
+                exit_tile = (i, distance);
+            }
+        }
+    }
+    map.populate_blocked();
+
+    exit_tile.0
+}
+
+/// Digs an L-shaped tunnel from every pocket of floor the player can't reach to the nearest
+/// tile they can, until the whole level is connected to `start_idx`.
+pub fn connect_unreachable_areas(map: &mut Map, start_idx: usize) {
+    loop {
+        map.populate_blocked();
+        let dijkstra_map =
+            rltk::DijkstraMap::new(map.width, map.height, &[start_idx], map, 10_000.0);
+        let reachable = |i: usize| dijkstra_map.map[i] < f32::MAX;
+
+        let stranded = map
+            .tiles
+            .iter()
+            .enumerate()
+            .position(|(i, tile)| *tile != TileType::Wall && !reachable(i));
+        let stranded = match stranded {
+            None => return,
+            Some(idx) => idx as i32,
+        };
+        let (x1, y1) = (stranded % map.width, stranded / map.width);
+
+        let nearest = (0..map.tiles.len())
+            .filter(|i| reachable(*i))
+            .map(|i| i as i32)
+            .min_by_key(|i| (i % map.width - x1).abs() + (i / map.width - y1).abs())
+            .unwrap_or(start_idx as i32);
+        let (x2, y2) = (nearest % map.width, nearest / map.width);
+
+        apply_horizontal_tunnel(map, x1, x2, y1);
+        apply_vertical_tunnel(map, y1, y2, x2);
+    }
+}
+
+/// Spawn regions for room-based maps: the floor of every room except the first, which is
+/// where the player starts.
+pub fn room_spawn_regions(map: &Map, rooms: &[Rect]) -> Vec<Vec<usize>> {
+    rooms
+        .iter()
+        .skip(1)
+        .map(|room| {
+            let mut region = Vec::new();
+            for y in room.y1 + 1..=room.y2 {
+                for x in room.x1 + 1..=room.x2 {
+                    region.push(map.xy_idx(x, y));
+                }
+            }
+            region
+        })
+        .collect()
+}
+
+/// Spawn regions for open-plan maps: the floor tiles of each square chunk of the map,
+/// leaving out the player's starting tile.
+pub fn chunk_spawn_regions(map: &Map, start_idx: usize) -> Vec<Vec<usize>> {
+    let mut regions = Vec::new();
+    for chunk_y in (0..map.height).step_by(REGION_SIZE as usize) {
+        for chunk_x in (0..map.width).step_by(REGION_SIZE as usize) {
+            let mut region = Vec::new();
+            for y in chunk_y..min(chunk_y + REGION_SIZE, map.height) {
+                for x in chunk_x..min(chunk_x + REGION_SIZE, map.width) {
+                    let idx = map.xy_idx(x, y);
+                    if map.tiles[idx] == TileType::Floor && idx != start_idx {
+                        region.push(idx);
+                    }
+                }
+            }
+            if !region.is_empty() {
+                regions.push(region);
+            }
+        }
+    }
+    regions
+}
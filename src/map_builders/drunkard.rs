@@ -0,0 +1,99 @@
+use super::{
+    common::{chunk_spawn_regions, remove_unreachable_areas_returning_most_distant},
+    MapBuilder,
+};
+use crate::{Map, Position, TileType};
+use rltk::RandomNumberGenerator;
+
+/// Winding open caverns dug by "drunken" diggers that stagger around from the start until
+/// enough of the map is open.
+pub struct DrunkardsWalkBuilder {
+    map: Map,
+    starting_position: Position,
+}
+
+impl DrunkardsWalkBuilder {
+    pub fn new(new_depth: i32, width: i32, height: i32) -> DrunkardsWalkBuilder {
+        DrunkardsWalkBuilder {
+            map: Map::new(new_depth, width, height),
+            starting_position: Position { x: 0, y: 0 },
+        }
+    }
+}
+
+impl MapBuilder for DrunkardsWalkBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        const DRUNKEN_LIFETIME: i32 = 400;
+        const FLOOR_PERCENT: f32 = 0.5;
+
+        let width = self.map.width;
+        let height = self.map.height;
+
+        self.starting_position = Position {
+            x: width / 2,
+            y: height / 2,
+        };
+        let start_idx = self
+            .map
+            .xy_idx(self.starting_position.x, self.starting_position.y);
+        self.map.tiles[start_idx] = TileType::Floor;
+
+        let total_tiles = self.map.tiles.len();
+        let desired_floor_tiles = (FLOOR_PERCENT * total_tiles as f32) as usize;
+        let mut floor_tile_count = 1;
+
+        while floor_tile_count < desired_floor_tiles {
+            let mut drunk_x = self.starting_position.x;
+            let mut drunk_y = self.starting_position.y;
+
+            for _ in 0..DRUNKEN_LIFETIME {
+                let idx = self.map.xy_idx(drunk_x, drunk_y);
+                if self.map.tiles[idx] == TileType::Wall {
+                    self.map.tiles[idx] = TileType::Floor;
+                    floor_tile_count += 1;
+                }
+
+                match rng.roll_dice(1, 4) {
+                    1 => {
+                        if drunk_x > 2 {
+                            drunk_x -= 1;
+                        }
+                    }
+                    2 => {
+                        if drunk_x < width - 2 {
+                            drunk_x += 1;
+                        }
+                    }
+                    3 => {
+                        if drunk_y > 2 {
+                            drunk_y -= 1;
+                        }
+                    }
+                    _ => {
+                        if drunk_y < height - 2 {
+                            drunk_y += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        let exit_idx = remove_unreachable_areas_returning_most_distant(&mut self.map, start_idx);
+        self.map.tiles[exit_idx] = TileType::DownStairs;
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_regions(&self) -> Vec<Vec<usize>> {
+        let start_idx = self
+            .map
+            .xy_idx(self.starting_position.x, self.starting_position.y);
+        chunk_spawn_regions(&self.map, start_idx)
+    }
+}
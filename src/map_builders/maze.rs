@@ -0,0 +1,81 @@
+use super::{
+    common::{chunk_spawn_regions, remove_unreachable_areas_returning_most_distant},
+    MapBuilder,
+};
+use crate::{Map, Position, TileType};
+use rltk::RandomNumberGenerator;
+
+/// A perfect maze carved with a recursive backtracker. Cells sit on odd coordinates and
+/// the walls between them are knocked out as the walk visits each neighbour.
+pub struct MazeBuilder {
+    map: Map,
+    starting_position: Position,
+}
+
+impl MazeBuilder {
+    pub fn new(new_depth: i32, width: i32, height: i32) -> MazeBuilder {
+        MazeBuilder {
+            map: Map::new(new_depth, width, height),
+            starting_position: Position { x: 0, y: 0 },
+        }
+    }
+}
+
+impl MapBuilder for MazeBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        let cells_x = (self.map.width - 1) / 2;
+        let cells_y = (self.map.height - 1) / 2;
+        let cell_idx = |cx: i32, cy: i32| (cy * cells_x + cx) as usize;
+
+        let mut visited = vec![false; (cells_x * cells_y) as usize];
+        let mut backtrace: Vec<(i32, i32)> = vec![(0, 0)];
+        visited[0] = true;
+        let idx = self.map.xy_idx(1, 1);
+        self.map.tiles[idx] = TileType::Floor;
+
+        while let Some(&(cx, cy)) = backtrace.last() {
+            let mut neighbors = Vec::new();
+            for (dx, dy) in &[(0, -1), (1, 0), (0, 1), (-1, 0)] {
+                let (nx, ny) = (cx + dx, cy + dy);
+                if nx >= 0 && nx < cells_x && ny >= 0 && ny < cells_y && !visited[cell_idx(nx, ny)]
+                {
+                    neighbors.push((nx, ny));
+                }
+            }
+
+            match rng.random_slice_entry(&neighbors) {
+                None => {
+                    backtrace.pop();
+                }
+                Some(&(nx, ny)) => {
+                    visited[cell_idx(nx, ny)] = true;
+                    let wall_idx = self.map.xy_idx(cx + nx + 1, cy + ny + 1);
+                    let cell_tile = self.map.xy_idx(nx * 2 + 1, ny * 2 + 1);
+                    self.map.tiles[wall_idx] = TileType::Floor;
+                    self.map.tiles[cell_tile] = TileType::Floor;
+                    backtrace.push((nx, ny));
+                }
+            }
+        }
+
+        self.starting_position = Position { x: 1, y: 1 };
+        let start_idx = self.map.xy_idx(1, 1);
+        let exit_idx = remove_unreachable_areas_returning_most_distant(&mut self.map, start_idx);
+        self.map.tiles[exit_idx] = TileType::DownStairs;
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_regions(&self) -> Vec<Vec<usize>> {
+        let start_idx = self
+            .map
+            .xy_idx(self.starting_position.x, self.starting_position.y);
+        chunk_spawn_regions(&self.map, start_idx)
+    }
+}
@@ -0,0 +1,113 @@
+use super::{
+    camera::{VIEW_HEIGHT, VIEW_WIDTH},
+    Map, Position,
+};
+use rltk::RandomNumberGenerator;
+
+mod bsp_dungeon;
+mod cellular_automata;
+mod common;
+mod drunkard;
+mod maze;
+pub mod prefab;
+mod prefab_builder;
+mod simple_map;
+mod waveform_collapse;
+use bsp_dungeon::BspDungeonBuilder;
+use cellular_automata::CellularAutomataBuilder;
+use drunkard::DrunkardsWalkBuilder;
+use maze::MazeBuilder;
+use prefab_builder::PrefabBuilder;
+use simple_map::SimpleMapBuilder;
+use waveform_collapse::{WaveformCollapseBuilder, WFC_SAMPLES};
+
+/// The first level fits the screen exactly; each level below it is a little bigger, and the
+/// camera scrolls to follow the player.
+pub fn level_size(new_depth: i32) -> (i32, i32) {
+    let growth = i32::max(0, new_depth - 1);
+    (
+        i32::min(VIEW_WIDTH + growth * 8, VIEW_WIDTH * 2),
+        i32::min(VIEW_HEIGHT + growth * 4, VIEW_HEIGHT * 2),
+    )
+}
+
+pub trait MapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator);
+    fn get_map(&self) -> Map;
+    fn get_starting_position(&self) -> Position;
+    /// Groups of tile indices that spawns are placed into, one roll of the spawn table per
+    /// group. The player's starting tile is never part of a region.
+    fn get_spawn_regions(&self) -> Vec<Vec<usize>>;
+    /// Entities placed by hand, by tile index and raws name, on top of the spawn regions.
+    fn get_spawn_list(&self) -> Vec<(usize, String)> {
+        Vec::new()
+    }
+}
+
+/// Picks the generator for a level. The first level is always the classic rooms and
+/// corridors; deeper levels draw from every algorithm, sometimes use a hand-made level, and
+/// may have a vault stamped in.
+pub fn builder_for_depth(new_depth: i32, rng: &mut RandomNumberGenerator) -> Box<dyn MapBuilder> {
+    let (width, height) = level_size(new_depth);
+    if new_depth == 1 {
+        return Box::new(SimpleMapBuilder::new(new_depth, width, height));
+    }
+
+    let builder: Box<dyn MapBuilder> = match rng.roll_dice(1, 7) {
+        1 => Box::new(SimpleMapBuilder::new(new_depth, width, height)),
+        2 => Box::new(BspDungeonBuilder::new(new_depth, width, height)),
+        3 => Box::new(CellularAutomataBuilder::new(new_depth, width, height)),
+        4 => Box::new(DrunkardsWalkBuilder::new(new_depth, width, height)),
+        5 => Box::new(MazeBuilder::new(new_depth, width, height)),
+        6 => {
+            let sample = rng.random_slice_entry(WFC_SAMPLES).unwrap();
+            Box::new(WaveformCollapseBuilder::new(
+                new_depth, width, height, sample,
+            ))
+        }
+        _ => {
+            let path = rng.random_slice_entry(prefab::LEVELS).unwrap();
+            return Box::new(PrefabBuilder::level(new_depth, path));
+        }
+    };
+
+    if rng.roll_dice(1, 2) == 1 {
+        Box::new(PrefabBuilder::vaults(new_depth, builder))
+    } else {
+        builder
+    }
+}
+
+/// Every available generator, for tools and tests that want to exercise them all. Hand-made
+/// levels come at their own size.
+pub fn all_builders(new_depth: i32, width: i32, height: i32) -> Vec<Box<dyn MapBuilder>> {
+    vec![
+        Box::new(SimpleMapBuilder::new(new_depth, width, height)),
+        Box::new(BspDungeonBuilder::new(new_depth, width, height)),
+        Box::new(CellularAutomataBuilder::new(new_depth, width, height)),
+        Box::new(DrunkardsWalkBuilder::new(new_depth, width, height)),
+        Box::new(MazeBuilder::new(new_depth, width, height)),
+        Box::new(WaveformCollapseBuilder::new(
+            new_depth,
+            width,
+            height,
+            &WFC_SAMPLES[0],
+        )),
+        Box::new(WaveformCollapseBuilder::new(
+            new_depth,
+            width,
+            height,
+            &WFC_SAMPLES[1],
+        )),
+        Box::new(PrefabBuilder::level(new_depth, prefab::LEVELS[0])),
+    ]
+}
+
+/// Every generated algorithm with a vault stamped in, where one fits.
+pub fn all_vault_builders(new_depth: i32, width: i32, height: i32) -> Vec<Box<dyn MapBuilder>> {
+    all_builders(new_depth, width, height)
+        .into_iter()
+        .take(7)
+        .map(|builder| Box::new(PrefabBuilder::vaults(new_depth, builder)) as Box<dyn MapBuilder>)
+        .collect()
+}
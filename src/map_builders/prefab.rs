@@ -0,0 +1,143 @@
+use crate::TileType;
+use rltk::rex::XpFile;
+
+rltk::embedded_resource!(WFC_POPULATED, "../../resources/wfc-populated.xp");
+rltk::embedded_resource!(VAULT_TREASURY, "../../resources/vault-treasury.xp");
+rltk::embedded_resource!(VAULT_ORC_DEN, "../../resources/vault-orc-den.xp");
+rltk::embedded_resource!(WFC_DEMO1, "../../resources/wfc-demo1.xp");
+rltk::embedded_resource!(WFC_DEMO2, "../../resources/wfc-demo2.xp");
+
+/// Hand-made levels that can replace a generated map outright.
+pub const LEVELS: &[&str] = &["../../resources/wfc-populated.xp"];
+
+/// A small hand-made room stamped into a generated map on levels within its depth range.
+pub struct VaultDef {
+    pub path: &'static str,
+    pub min_depth: i32,
+    pub max_depth: i32,
+}
+
+pub const VAULTS: &[VaultDef] = &[
+    VaultDef {
+        path: "../../resources/vault-treasury.xp",
+        min_depth: 2,
+        max_depth: 100,
+    },
+    VaultDef {
+        path: "../../resources/vault-orc-den.xp",
+        min_depth: 3,
+        max_depth: 100,
+    },
+];
+
+/// What a single REX glyph stands for: the tile underneath, plus whatever stands on it.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct PrefabCell {
+    pub tile: TileType,
+    pub spawn: Option<&'static str>,
+    pub player_start: bool,
+}
+
+impl PrefabCell {
+    fn floor_with(spawn: &'static str) -> PrefabCell {
+        PrefabCell {
+            tile: TileType::Floor,
+            spawn: Some(spawn),
+            player_start: false,
+        }
+    }
+}
+
+/// The legend shared by every prefab. Spawn names refer to entities in the raws.
+pub fn glyph_to_cell(glyph: char) -> Option<PrefabCell> {
+    let tile = |tile| PrefabCell {
+        tile,
+        spawn: None,
+        player_start: false,
+    };
+    match glyph {
+        ' ' => Some(tile(TileType::Floor)),
+        '#' => Some(tile(TileType::Wall)),
+        '>' => Some(tile(TileType::DownStairs)),
+        '@' => Some(PrefabCell {
+            player_start: true,
+            ..tile(TileType::Floor)
+        }),
+        'g' => Some(PrefabCell::floor_with("Goblin")),
+        'o' => Some(PrefabCell::floor_with("Orc")),
+        '!' => Some(PrefabCell::floor_with("Health Potion")),
+        '%' => Some(PrefabCell::floor_with("Rations")),
+        '^' => Some(PrefabCell::floor_with("Smack Trap")),
+        _ => None,
+    }
+}
+
+/// A REX Paint image flattened to one glyph per cell, row by row.
+pub struct Prefab {
+    pub width: i32,
+    pub height: i32,
+    glyphs: Vec<char>,
+}
+
+impl Prefab {
+    /// Loads one of the prefabs or sample images bundled into the binary. They ship with the
+    /// game, so a missing or corrupt file fails loudly, like the raws do.
+    pub fn load(path: &str) -> Prefab {
+        rltk::link_resource!(WFC_POPULATED, "../../resources/wfc-populated.xp");
+        rltk::link_resource!(VAULT_TREASURY, "../../resources/vault-treasury.xp");
+        rltk::link_resource!(VAULT_ORC_DEN, "../../resources/vault-orc-den.xp");
+        rltk::link_resource!(WFC_DEMO1, "../../resources/wfc-demo1.xp");
+        rltk::link_resource!(WFC_DEMO2, "../../resources/wfc-demo2.xp");
+
+        let xp = XpFile::from_resource(path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+        Prefab::from_xp(&xp)
+    }
+
+    /// Flattens every layer of `xp`, later layers drawing over earlier ones except where
+    /// their background is REX Paint's transparent pink.
+    pub fn from_xp(xp: &XpFile) -> Prefab {
+        let width = xp.layers.iter().map(|l| l.width).max().unwrap_or(0);
+        let height = xp.layers.iter().map(|l| l.height).max().unwrap_or(0);
+        let mut glyphs = vec![' '; width * height];
+
+        for layer in xp.layers.iter() {
+            for y in 0..layer.height {
+                for x in 0..layer.width {
+                    let cell = layer.get(x, y).unwrap();
+                    if !cell.bg.is_transparent() {
+                        glyphs[y * width + x] = rltk::to_char(cell.ch as u8);
+                    }
+                }
+            }
+        }
+
+        Prefab {
+            width: width as i32,
+            height: height as i32,
+            glyphs,
+        }
+    }
+
+    pub fn glyph(&self, x: i32, y: i32) -> char {
+        self.glyphs[(y * self.width + x) as usize]
+    }
+
+    /// The decoded cell at (x, y). Glyphs outside the legend read as plain floor; `check`
+    /// reports them.
+    pub fn cell(&self, x: i32, y: i32) -> PrefabCell {
+        glyph_to_cell(self.glyph(x, y)).unwrap_or_else(|| glyph_to_cell(' ').unwrap())
+    }
+
+    /// Fails on the first glyph that isn't in the legend, saying where it was found.
+    pub fn check(&self) -> Result<(), String> {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let glyph = self.glyph(x, y);
+                if glyph_to_cell(glyph).is_none() {
+                    return Err(format!("unknown glyph {:?} at ({}, {})", glyph, x, y));
+                }
+            }
+        }
+        Ok(())
+    }
+}
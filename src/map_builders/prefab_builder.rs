@@ -0,0 +1,204 @@
+use super::{
+    prefab::{Prefab, VAULTS},
+    MapBuilder,
+};
+use crate::{Map, Position, TileType};
+use rltk::RandomNumberGenerator;
+use std::collections::HashSet;
+
+/// How many spots a vault is tried in before the level goes without one.
+const VAULT_ATTEMPTS: i32 = 50;
+
+pub enum PrefabMode {
+    /// Use a hand-made level as the whole map.
+    Level { path: &'static str },
+    /// Build a map with another builder, then stamp a vault into it.
+    Vaults,
+}
+
+pub struct PrefabBuilder {
+    map: Map,
+    starting_position: Position,
+    depth: i32,
+    mode: PrefabMode,
+    previous_builder: Option<Box<dyn MapBuilder>>,
+    spawn_regions: Vec<Vec<usize>>,
+    spawn_list: Vec<(usize, String)>,
+}
+
+impl PrefabBuilder {
+    pub fn level(new_depth: i32, path: &'static str) -> PrefabBuilder {
+        PrefabBuilder::with_mode(new_depth, PrefabMode::Level { path }, None)
+    }
+
+    pub fn vaults(new_depth: i32, previous_builder: Box<dyn MapBuilder>) -> PrefabBuilder {
+        PrefabBuilder::with_mode(new_depth, PrefabMode::Vaults, Some(previous_builder))
+    }
+
+    fn with_mode(
+        new_depth: i32,
+        mode: PrefabMode,
+        previous_builder: Option<Box<dyn MapBuilder>>,
+    ) -> PrefabBuilder {
+        PrefabBuilder {
+            map: Map::default(),
+            starting_position: Position { x: 0, y: 0 },
+            depth: new_depth,
+            mode,
+            previous_builder,
+            spawn_regions: Vec::new(),
+            spawn_list: Vec::new(),
+        }
+    }
+
+    /// Loads a hand-made level, sizing the map to fit it.
+    fn load_level(&mut self, path: &str) {
+        let prefab = Prefab::load(path);
+        self.map = Map::new(self.depth, prefab.width, prefab.height);
+        let mut start = None;
+
+        for y in 0..prefab.height {
+            for x in 0..prefab.width {
+                let cell = prefab.cell(x, y);
+                let idx = self.map.xy_idx(x, y);
+                self.map.tiles[idx] = cell.tile;
+                if let Some(name) = cell.spawn {
+                    self.spawn_list.push((idx, name.to_string()));
+                }
+                if cell.player_start {
+                    start = Some(Position { x, y });
+                }
+            }
+        }
+
+        self.starting_position =
+            start.unwrap_or_else(|| panic!("{}: level has no @ starting position", path));
+    }
+
+    fn apply_vaults(&mut self, rng: &mut RandomNumberGenerator) {
+        let previous_builder = self
+            .previous_builder
+            .as_mut()
+            .expect("Vaults need a map to go into");
+        previous_builder.build_map(rng);
+        self.map = previous_builder.get_map();
+        self.starting_position = previous_builder.get_starting_position();
+        self.spawn_regions = previous_builder.get_spawn_regions();
+        self.spawn_list = previous_builder.get_spawn_list();
+
+        let depth = self.depth;
+        let candidates: Vec<&'static str> = VAULTS
+            .iter()
+            .filter(|vault| depth >= vault.min_depth && depth <= vault.max_depth)
+            .map(|vault| vault.path)
+            .collect();
+        let path = match rng.random_slice_entry(&candidates) {
+            None => return,
+            Some(path) => *path,
+        };
+
+        let prefab = Prefab::load(path);
+        if prefab.width > self.map.width - 2 || prefab.height > self.map.height - 2 {
+            return;
+        }
+        for _ in 0..VAULT_ATTEMPTS {
+            let x = rng.roll_dice(1, self.map.width - prefab.width - 1);
+            let y = rng.roll_dice(1, self.map.height - prefab.height - 1);
+            if self.try_stamp(&prefab, x, y) {
+                return;
+            }
+        }
+    }
+
+    /// Stamps `prefab` with its top-left corner at (x, y) if its open cells all land on
+    /// floor and the level stays fully connected afterwards. Returns whether it was placed.
+    fn try_stamp(&mut self, prefab: &Prefab, x: i32, y: i32) -> bool {
+        let start_idx = self
+            .map
+            .xy_idx(self.starting_position.x, self.starting_position.y);
+
+        let mut footprint = Vec::new();
+        for py in 0..prefab.height {
+            for px in 0..prefab.width {
+                let idx = self.map.xy_idx(x + px, y + py);
+                let cell = prefab.cell(px, py);
+                let existing = self.map.tiles[idx];
+                if idx == start_idx || existing == TileType::DownStairs {
+                    return false;
+                }
+                if cell.tile != TileType::Wall && existing != TileType::Floor {
+                    return false;
+                }
+                footprint.push((idx, cell));
+            }
+        }
+
+        let original_tiles = self.map.tiles.clone();
+        for (idx, cell) in footprint.iter() {
+            self.map.tiles[*idx] = cell.tile;
+        }
+
+        if !self.all_open_tiles_reachable(start_idx) {
+            self.map.tiles = original_tiles;
+            self.map.populate_blocked();
+            return false;
+        }
+
+        for (idx, cell) in footprint.iter() {
+            if let Some(name) = cell.spawn {
+                self.spawn_list.push((*idx, name.to_string()));
+            }
+        }
+
+        // Vaults come fully furnished, so random spawns stay out of them
+        let covered: HashSet<usize> = footprint.iter().map(|(idx, _)| *idx).collect();
+        for region in self.spawn_regions.iter_mut() {
+            region.retain(|idx| !covered.contains(idx));
+        }
+        self.spawn_regions.retain(|region| !region.is_empty());
+
+        true
+    }
+
+    fn all_open_tiles_reachable(&mut self, start_idx: usize) -> bool {
+        self.map.populate_blocked();
+        let dijkstra_map = rltk::DijkstraMap::new(
+            self.map.width,
+            self.map.height,
+            &[start_idx],
+            &self.map,
+            10_000.0,
+        );
+        self.map
+            .tiles
+            .iter()
+            .enumerate()
+            .all(|(i, tile)| *tile == TileType::Wall || dijkstra_map.map[i] < f32::MAX)
+    }
+}
+
+impl MapBuilder for PrefabBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        match self.mode {
+            PrefabMode::Level { path } => self.load_level(path),
+            PrefabMode::Vaults => self.apply_vaults(rng),
+        }
+        self.map.populate_blocked();
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_regions(&self) -> Vec<Vec<usize>> {
+        self.spawn_regions.clone()
+    }
+
+    fn get_spawn_list(&self) -> Vec<(usize, String)> {
+        self.spawn_list.clone()
+    }
+}
@@ -0,0 +1,94 @@
+use super::{
+    common::{
+        apply_horizontal_tunnel, apply_room_to_map, apply_vertical_tunnel, central_room,
+        room_spawn_regions, stairs_position,
+    },
+    MapBuilder,
+};
+use crate::{Map, Position, Rect, TileType};
+use rltk::RandomNumberGenerator;
+
+/// Random non-overlapping rooms, each joined to the previous one by an L-shaped corridor.
+pub struct SimpleMapBuilder {
+    map: Map,
+    starting_position: Position,
+}
+
+impl SimpleMapBuilder {
+    pub fn new(new_depth: i32, width: i32, height: i32) -> SimpleMapBuilder {
+        SimpleMapBuilder {
+            map: Map::new(new_depth, width, height),
+            starting_position: Position { x: 0, y: 0 },
+        }
+    }
+}
+
+impl MapBuilder for SimpleMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        // Thirty attempts on a screen-sized map, more on bigger ones
+        let max_rooms = 30 * self.map.width * self.map.height / (80 * 43);
+        const MIN_SIZE: i32 = 6;
+        const MAX_SIZE: i32 = 10;
+
+        let map = &mut self.map;
+        for _ in 0..max_rooms {
+            let w = rng.range(MIN_SIZE, MAX_SIZE);
+            let h = rng.range(MIN_SIZE, MAX_SIZE);
+            let x = rng.roll_dice(1, map.width - w - 1) - 1;
+            let y = rng.roll_dice(1, map.height - h - 1) - 1;
+            let new_room = Rect::new(x, y, w, h);
+            let mut ok = true;
+            for other_room in map.rooms.iter() {
+                if new_room.intersect(other_room) {
+                    ok = false
+                }
+            }
+            if ok {
+                apply_room_to_map(map, &new_room);
+
+                if let Some(prev_room) = map.rooms.last() {
+                    let (new_x, new_y) = new_room.center();
+                    let (prev_x, prev_y) = prev_room.center();
+                    if rng.range(0, 2) == 1 {
+                        apply_horizontal_tunnel(map, prev_x, new_x, prev_y);
+                        apply_vertical_tunnel(map, prev_y, new_y, new_x);
+                    } else {
+                        apply_vertical_tunnel(map, prev_y, new_y, prev_x);
+                        apply_horizontal_tunnel(map, prev_x, new_x, new_y);
+                    }
+                }
+
+                map.rooms.push(new_room);
+            }
+        }
+
+        // Too small a map for any room to fit still gets one
+        if map.rooms.is_empty() {
+            let room = central_room(map);
+            apply_room_to_map(map, &room);
+            map.rooms.push(room);
+        }
+
+        let stairs = stairs_position(&map.rooms);
+        let stairs_idx = map.xy_idx(stairs.0, stairs.1);
+        map.tiles[stairs_idx] = TileType::DownStairs;
+
+        let (start_x, start_y) = map.rooms[0].center();
+        self.starting_position = Position {
+            x: start_x,
+            y: start_y,
+        };
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_regions(&self) -> Vec<Vec<usize>> {
+        room_spawn_regions(&self.map, &self.map.rooms)
+    }
+}
@@ -0,0 +1,382 @@
+use super::{
+    common::{
+        chunk_spawn_regions, connect_unreachable_areas,
+        remove_unreachable_areas_returning_most_distant,
+    },
+    prefab::Prefab,
+    MapBuilder,
+};
+use crate::{random_table::RandomTable, Map, Position, TileType};
+use rltk::RandomNumberGenerator;
+
+/// How many times the solver starts over after painting itself into a corner before it
+/// settles for whatever it has.
+const SOLVER_ATTEMPTS: i32 = 20;
+
+/// A sample image to learn patterns from, and the size of the square chunks it is cut into.
+pub struct WfcSample {
+    pub path: &'static str,
+    pub chunk_size: i32,
+}
+
+pub const WFC_SAMPLES: &[WfcSample] = &[
+    WfcSample {
+        path: "../../resources/wfc-demo1.xp",
+        chunk_size: 8,
+    },
+    WfcSample {
+        path: "../../resources/wfc-demo2.xp",
+        chunk_size: 7,
+    },
+];
+
+const NORTH: usize = 0;
+const EAST: usize = 1;
+const SOUTH: usize = 2;
+const WEST: usize = 3;
+const OPPOSITE: [usize; 4] = [SOUTH, WEST, NORTH, EAST];
+
+/// One square of the sample. Chunks sit side by side in the output, so two may touch only
+/// when the tiles along their shared edge are open in the same places.
+#[derive(PartialEq, Clone)]
+struct Chunk {
+    tiles: Vec<TileType>,
+    /// Open (non-wall) tiles along the north, east, south and west edges.
+    edges: [Vec<bool>; 4],
+}
+
+impl Chunk {
+    fn new(tiles: Vec<TileType>, size: usize) -> Chunk {
+        let open = |x: usize, y: usize| tiles[y * size + x] != TileType::Wall;
+        let edges = [
+            (0..size).map(|x| open(x, 0)).collect(),
+            (0..size).map(|y| open(size - 1, y)).collect(),
+            (0..size).map(|x| open(x, size - 1)).collect(),
+            (0..size).map(|y| open(0, y)).collect(),
+        ];
+        Chunk { tiles, edges }
+    }
+
+    fn mirrored(&self, size: usize, horizontal: bool) -> Chunk {
+        let mut tiles = Vec::with_capacity(size * size);
+        for y in 0..size {
+            for x in 0..size {
+                let (sx, sy) = if horizontal {
+                    (size - 1 - x, y)
+                } else {
+                    (x, size - 1 - y)
+                };
+                tiles.push(self.tiles[sy * size + sx]);
+            }
+        }
+        Chunk::new(tiles, size)
+    }
+}
+
+/// Cuts the sample into chunks, adding mirror images for variety. Each distinct chunk is
+/// returned with how often it turned up, which weights how often it is picked.
+fn build_patterns(sample: &Prefab, chunk_size: i32) -> Vec<(Chunk, i32)> {
+    let size = chunk_size as usize;
+    let mut patterns: Vec<(Chunk, i32)> = Vec::new();
+    let mut add = |chunk: Chunk| match patterns.iter_mut().find(|(c, _)| *c == chunk) {
+        Some((_, count)) => *count += 1,
+        None => patterns.push((chunk, 1)),
+    };
+
+    for cy in 0..sample.height / chunk_size {
+        for cx in 0..sample.width / chunk_size {
+            let mut tiles = Vec::with_capacity(size * size);
+            for y in 0..chunk_size {
+                for x in 0..chunk_size {
+                    tiles.push(sample.cell(cx * chunk_size + x, cy * chunk_size + y).tile);
+                }
+            }
+            let chunk = Chunk::new(tiles, size);
+            add(chunk.mirrored(size, true));
+            add(chunk.mirrored(size, false));
+            add(chunk.mirrored(size, true).mirrored(size, false));
+            add(chunk);
+        }
+    }
+    patterns
+}
+
+/// Numbers every distinct edge, so each pattern's four edges become ids that can be
+/// compared cheaply. Returns the ids for each pattern and how many distinct edges there are.
+fn build_edge_ids(patterns: &[(Chunk, i32)]) -> (Vec<[usize; 4]>, usize) {
+    let mut edges: Vec<&Vec<bool>> = Vec::new();
+    let ids = patterns
+        .iter()
+        .map(|(chunk, _)| {
+            let mut ids = [0; 4];
+            for (dir, edge) in chunk.edges.iter().enumerate() {
+                ids[dir] = match edges.iter().position(|e| *e == edge) {
+                    Some(id) => id,
+                    None => {
+                        edges.push(edge);
+                        edges.len() - 1
+                    }
+                };
+            }
+            ids
+        })
+        .collect();
+    (ids, edges.len())
+}
+
+/// The chunk grid being solved: each cell keeps the patterns it could still become.
+struct Solver<'a> {
+    patterns: &'a [(Chunk, i32)],
+    edge_ids: &'a [[usize; 4]],
+    edge_count: usize,
+    width: i32,
+    height: i32,
+    possible: Vec<Vec<bool>>,
+}
+
+impl<'a> Solver<'a> {
+    fn new(
+        patterns: &'a [(Chunk, i32)],
+        edge_ids: &'a [[usize; 4]],
+        edge_count: usize,
+        width: i32,
+        height: i32,
+    ) -> Solver<'a> {
+        Solver {
+            patterns,
+            edge_ids,
+            edge_count,
+            width,
+            height,
+            possible: vec![vec![true; patterns.len()]; (width * height) as usize],
+        }
+    }
+
+    fn options(&self, cell: usize) -> usize {
+        self.possible[cell].iter().filter(|p| **p).count()
+    }
+
+    fn neighbour(&self, cell: usize, dir: usize) -> Option<usize> {
+        let x = cell as i32 % self.width;
+        let y = cell as i32 / self.width;
+        let (nx, ny) = match dir {
+            NORTH => (x, y - 1),
+            EAST => (x + 1, y),
+            SOUTH => (x, y + 1),
+            _ => (x - 1, y),
+        };
+        if nx < 0 || nx >= self.width || ny < 0 || ny >= self.height {
+            None
+        } else {
+            Some((ny * self.width + nx) as usize)
+        }
+    }
+
+    /// Collapses the cell with the fewest options left, over and over, until every cell is
+    /// decided. Returns false if some cell ran out of options.
+    fn solve(&mut self, rng: &mut RandomNumberGenerator) -> bool {
+        loop {
+            let mut lowest = usize::MAX;
+            let mut candidates = Vec::new();
+            for cell in 0..self.possible.len() {
+                let options = self.options(cell);
+                if options == 0 {
+                    return false;
+                }
+                if options > 1 {
+                    if options < lowest {
+                        lowest = options;
+                        candidates.clear();
+                    }
+                    if options == lowest {
+                        candidates.push(cell);
+                    }
+                }
+            }
+
+            let cell = match rng.random_slice_entry(&candidates) {
+                None => return true,
+                Some(cell) => *cell,
+            };
+            self.collapse(cell, rng);
+            if !self.propagate(cell) {
+                return false;
+            }
+        }
+    }
+
+    fn collapse(&mut self, cell: usize, rng: &mut RandomNumberGenerator) {
+        let mut table = RandomTable::new();
+        for (p, (_, count)) in self.patterns.iter().enumerate() {
+            if self.possible[cell][p] {
+                table = table.add(p, *count);
+            }
+        }
+        let chosen = *table.roll(rng).expect("Cell has options left");
+        for (p, possible) in self.possible[cell].iter_mut().enumerate() {
+            *possible = p == chosen;
+        }
+    }
+
+    /// Strikes out every pattern that no longer fits next to its neighbours, rippling out
+    /// from `cell`. Returns false on a contradiction.
+    fn propagate(&mut self, cell: usize) -> bool {
+        let mut stack = vec![cell];
+        while let Some(cell) = stack.pop() {
+            for dir in 0..4 {
+                let neighbour = match self.neighbour(cell, dir) {
+                    None => continue,
+                    Some(neighbour) => neighbour,
+                };
+
+                // Edges this cell can still show towards the neighbour
+                let mut offered = vec![false; self.edge_count];
+                for p in (0..self.patterns.len()).filter(|p| self.possible[cell][*p]) {
+                    offered[self.edge_ids[p][dir]] = true;
+                }
+
+                let mut changed = false;
+                for (q, possible) in self.possible[neighbour].iter_mut().enumerate() {
+                    if *possible && !offered[self.edge_ids[q][OPPOSITE[dir]]] {
+                        *possible = false;
+                        changed = true;
+                    }
+                }
+
+                if changed {
+                    if self.options(neighbour) == 0 {
+                        return false;
+                    }
+                    stack.push(neighbour);
+                }
+            }
+        }
+        true
+    }
+
+    /// The pattern each cell settled on, if it settled at all.
+    fn chosen(&self, cell: usize) -> Option<usize> {
+        if self.options(cell) == 1 {
+            self.possible[cell].iter().position(|p| *p)
+        } else {
+            None
+        }
+    }
+}
+
+/// Wave function collapse: the sample image is cut into chunks, and the map is tiled with
+/// chunks whose edges line up, picking the most constrained spot first each time.
+pub struct WaveformCollapseBuilder {
+    map: Map,
+    starting_position: Position,
+    sample: &'static WfcSample,
+}
+
+impl WaveformCollapseBuilder {
+    pub fn new(
+        new_depth: i32,
+        width: i32,
+        height: i32,
+        sample: &'static WfcSample,
+    ) -> WaveformCollapseBuilder {
+        WaveformCollapseBuilder {
+            map: Map::new(new_depth, width, height),
+            starting_position: Position { x: 0, y: 0 },
+            sample,
+        }
+    }
+
+    fn apply_solution(&mut self, solver: &Solver, patterns: &[(Chunk, i32)]) {
+        let size = self.sample.chunk_size;
+        for cell in 0..solver.possible.len() {
+            let pattern = match solver.chosen(cell) {
+                None => continue,
+                Some(pattern) => &patterns[pattern].0,
+            };
+            let cx = cell as i32 % solver.width;
+            let cy = cell as i32 / solver.width;
+            for y in 0..size {
+                for x in 0..size {
+                    let idx = self.map.xy_idx(cx * size + x, cy * size + y);
+                    self.map.tiles[idx] = pattern.tiles[(y * size + x) as usize];
+                }
+            }
+        }
+    }
+}
+
+impl MapBuilder for WaveformCollapseBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        let sample = Prefab::load(self.sample.path);
+        let patterns = build_patterns(&sample, self.sample.chunk_size);
+        let (edge_ids, edge_count) = build_edge_ids(&patterns);
+
+        let width = self.map.width;
+        let height = self.map.height;
+        let chunks_x = width / self.sample.chunk_size;
+        let chunks_y = height / self.sample.chunk_size;
+
+        let mut solver = Solver::new(&patterns, &edge_ids, edge_count, chunks_x, chunks_y);
+        for _ in 0..SOLVER_ATTEMPTS {
+            solver = Solver::new(&patterns, &edge_ids, edge_count, chunks_x, chunks_y);
+            if solver.solve(rng) {
+                break;
+            }
+        }
+        self.apply_solution(&solver, &patterns);
+
+        // Chunks only tile the map approximately, so seal the edges
+        for x in 0..width {
+            for y in &[0, height - 1] {
+                let idx = self.map.xy_idx(x, *y);
+                self.map.tiles[idx] = TileType::Wall;
+            }
+        }
+        for y in 0..height {
+            for x in &[0, width - 1] {
+                let idx = self.map.xy_idx(*x, y);
+                self.map.tiles[idx] = TileType::Wall;
+            }
+        }
+
+        // Start on the open tile nearest the middle
+        let (mid_x, mid_y) = (width / 2, height / 2);
+        let nearest_floor = (0..self.map.tiles.len())
+            .filter(|i| self.map.tiles[*i] == TileType::Floor)
+            .min_by_key(|i| {
+                let i = *i as i32;
+                (i % width - mid_x).abs() + (i / width - mid_y).abs()
+            });
+        let start_idx = match nearest_floor {
+            Some(idx) => idx,
+            None => {
+                let idx = self.map.xy_idx(mid_x, mid_y);
+                self.map.tiles[idx] = TileType::Floor;
+                idx
+            }
+        };
+        self.starting_position = Position {
+            x: start_idx as i32 % width,
+            y: start_idx as i32 / width,
+        };
+
+        connect_unreachable_areas(&mut self.map, start_idx);
+        let exit_idx = remove_unreachable_areas_returning_most_distant(&mut self.map, start_idx);
+        self.map.tiles[exit_idx] = TileType::DownStairs;
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_regions(&self) -> Vec<Vec<usize>> {
+        let start_idx = self
+            .map
+            .xy_idx(self.starting_position.x, self.starting_position.y);
+        chunk_spawn_regions(&self.map, start_idx)
+    }
+}
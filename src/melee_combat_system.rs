@@ -1,9 +1,14 @@
 extern crate specs;
 
 use super::{
-    gamelog::GameLog, particle_system::ParticleBuilder, CombatStats, DefenseBonus, Equipped,
-    HungerClock, HungerState, MeleePowerBonus, Name, Position, SufferDamage, WantsToMelee,
+    attributes,
+    combat::{self, AttackRoll},
+    gamelog::{GameLog, LogCategory, LogEntry},
+    particle_system::ParticleBuilder,
+    Attributes, CombatStats, DefenseBonus, Equipped, HungerClock, HungerState, MeleeWeapon, Name,
+    Position, Skills, SufferDamage, WantsToMelee,
 };
+use rltk::RandomNumberGenerator;
 use specs::prelude::*;
 
 pub struct MeleeCombatSystem {}
@@ -17,12 +22,15 @@ impl<'a> System<'a> for MeleeCombatSystem {
         ReadStorage<'a, Name>,
         ReadStorage<'a, CombatStats>,
         WriteStorage<'a, SufferDamage>,
-        ReadStorage<'a, MeleePowerBonus>,
+        ReadStorage<'a, MeleeWeapon>,
         ReadStorage<'a, DefenseBonus>,
         ReadStorage<'a, Equipped>,
         WriteStorage<'a, HungerClock>,
         WriteExpect<'a, ParticleBuilder>,
         ReadStorage<'a, Position>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, Skills>,
+        WriteExpect<'a, RandomNumberGenerator>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -33,31 +41,45 @@ impl<'a> System<'a> for MeleeCombatSystem {
             names,
             combat_stats,
             mut inflict_damage,
-            melee_power_bonus,
+            melee_weapons,
             defense_bonuses,
             equipped,
             hunger_clock,
             mut particle_builder,
             positions,
+            attributes,
+            skills,
+            mut rng,
         ) = data;
+        // Anything that fights without attributes or skills of its own is average and
+        // untrained
+        let attributes_of = |entity| attributes.get(entity).cloned().unwrap_or_default();
+        let skills_of = |entity| skills.get(entity).cloned().unwrap_or_default();
 
         for (_entity, wants_melee, name, stats) in
             (&entities, &wants_melee, &names, &combat_stats).join()
         {
             if stats.hp > 0 {
-                let mut offensive_bonus = 0;
-                for (_item_entity, power_bonus, equipped_by) in
-                    (&entities, &melee_power_bonus, &equipped).join()
+                let attacker_attributes = attributes_of(_entity);
+                let mut weapon_damage = combat::UNARMED_DAMAGE;
+                let mut damage_type = combat::UNARMED_DAMAGE_TYPE;
+                let mut attack_bonus =
+                    attributes::to_hit(&attacker_attributes, &skills_of(_entity));
+                for (_item_entity, weapon, equipped_by) in
+                    (&entities, &melee_weapons, &equipped).join()
                 {
                     if equipped_by.owner == _entity {
-                        offensive_bonus += power_bonus.power;
+                        weapon_damage = weapon.damage;
+                        damage_type = weapon.damage_type;
+                        attack_bonus += weapon.hit_bonus;
                     }
                 }
 
+                let mut damage_bonus = attributes::melee_damage(&attacker_attributes);
                 let hc = hunger_clock.get(_entity);
                 if let Some(hc) = hc {
                     if hc.state == HungerState::WellFed {
-                        offensive_bonus += 1;
+                        damage_bonus += 1;
                     }
                 }
 
@@ -65,14 +87,36 @@ impl<'a> System<'a> for MeleeCombatSystem {
                 if target_stats.hp > 0 {
                     let target_name = names.get(wants_melee.target).unwrap();
 
-                    let mut defensive_bonus = 0;
+                    let mut armor = 0;
                     for (_item_entity, defense_bonus, equipped_by) in
                         (&entities, &defense_bonuses, &equipped).join()
                     {
                         if equipped_by.owner == wants_melee.target {
-                            defensive_bonus += defense_bonus.defense;
+                            armor += defense_bonus.defense;
                         }
                     }
+                    let armor_class = combat::armor_class(
+                        attributes::dodge(
+                            &attributes_of(wants_melee.target),
+                            &skills_of(wants_melee.target),
+                        ),
+                        armor,
+                    );
+
+                    let natural = rng.roll_dice(1, 20);
+                    let roll = combat::resolve_attack(natural, attack_bonus, armor_class);
+                    if !roll.hits() {
+                        let entry = LogEntry::new(LogCategory::Combat).name(&name.name);
+                        log.push(if roll == AttackRoll::Fumble {
+                            entry
+                                .text(" fumbles an attack on ")
+                                .name(&target_name.name)
+                                .text("!")
+                        } else {
+                            entry.text(" misses ").name(&target_name.name)
+                        });
+                        continue;
+                    }
 
                     let pos = positions.get(wants_melee.target);
                     if let Some(pos) = pos {
@@ -86,22 +130,38 @@ impl<'a> System<'a> for MeleeCombatSystem {
                         );
                     }
 
-                    let damage = i32::max(
-                        0,
-                        (stats.power + offensive_bonus) - (target_stats.defense + defensive_bonus),
-                    );
+                    let critical = roll == AttackRoll::Critical;
+                    let damage =
+                        combat::roll_damage(&mut rng, weapon_damage, critical) + damage_bonus;
 
-                    if damage == 0 {
-                        log.entries.push(format!(
-                            "{} can't negatively affect {}",
-                            &name.name, &target_name.name
-                        ));
+                    if damage <= 0 {
+                        log.push(
+                            LogEntry::new(LogCategory::Combat)
+                                .name(&name.name)
+                                .text(" can't negatively affect ")
+                                .name(&target_name.name),
+                        );
                     } else {
-                        log.entries.push(format!(
-                            "{} hurts {}, for {} hp",
-                            &name.name, &target_name.name, damage
-                        ));
-                        SufferDamage::new_damage(&mut inflict_damage, wants_melee.target, damage);
+                        let entry = LogEntry::new(LogCategory::Combat).name(&name.name);
+                        let entry = if critical {
+                            entry.colored(" critically hits ", rltk::RGB::named(rltk::ORANGE))
+                        } else {
+                            entry.text(" hurts ")
+                        };
+                        log.push(
+                            entry
+                                .name(&target_name.name)
+                                .text(", for ")
+                                .damage(damage)
+                                .text(" hp"),
+                        );
+                        SufferDamage::new_damage(
+                            &mut inflict_damage,
+                            wants_melee.target,
+                            damage,
+                            Some(_entity),
+                            Some(damage_type),
+                        );
                     }
                 }
             }
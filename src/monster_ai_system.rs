@@ -1,10 +1,10 @@
 extern crate specs;
 
 use super::{
-    particle_system::ParticleBuilder, Confusion, EntityMoved, Map, Monster, Position, RunState,
-    Viewshed, WantsToMelee,
+    particle_system::ParticleBuilder, EntityMoved, Map, Monster, MyTurn, Position, RunState,
+    Skills, StatusEffects, StatusKind, Viewshed, WantsToMelee,
 };
-use specs::{prelude::*, storage::GenericWriteStorage};
+use specs::prelude::*;
 extern crate rltk;
 use rltk::Point;
 
@@ -22,9 +22,11 @@ impl<'a> System<'a> for MonsterAI {
         ReadStorage<'a, Monster>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, WantsToMelee>,
-        WriteStorage<'a, Confusion>,
+        ReadStorage<'a, StatusEffects>,
         WriteExpect<'a, ParticleBuilder>,
         WriteStorage<'a, EntityMoved>,
+        ReadStorage<'a, MyTurn>,
+        ReadStorage<'a, Skills>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -38,36 +40,42 @@ impl<'a> System<'a> for MonsterAI {
             monster,
             mut position,
             mut wants_to_melee,
-            mut confused,
+            status_effects,
             mut particle_builder,
             mut entity_moved,
+            my_turn,
+            skills,
         ) = data;
 
         if *runstate != RunState::MonsterTurn {
             return;
         }
+        // A stealthy player has to come that many tiles closer before monsters notice them
+        let stealth = skills
+            .get(*player_entity)
+            .map_or(0, |skills| skills.stealth);
 
-        for (entity, mut viewshed, _monster, mut pos) in
-            (&entities, &mut viewshed, &monster, &mut position).join()
+        for (entity, viewshed, _monster, pos, _turn) in
+            (&entities, &mut viewshed, &monster, &mut position, &my_turn).join()
         {
             let mut can_act = true;
 
-            let is_confused = confused.get_mut(entity);
-            if let Some(i_am_confused) = is_confused {
-                i_am_confused.turns -= 1;
-                if i_am_confused.turns < 1 {
-                    confused.remove(entity);
+            if let Some(effects) = status_effects.get(entity) {
+                if effects.has(StatusKind::Paralysis) {
+                    can_act = false;
                 }
-                can_act = false;
+                if effects.has(StatusKind::Confusion) {
+                    can_act = false;
 
-                particle_builder.request(
-                    pos.x,
-                    pos.y,
-                    rltk::RGB::named(rltk::MAGENTA),
-                    rltk::RGB::named(rltk::BLACK),
-                    rltk::to_cp437('?'),
-                    200.0,
-                );
+                    particle_builder.request(
+                        pos.x,
+                        pos.y,
+                        rltk::RGB::named(rltk::MAGENTA),
+                        rltk::RGB::named(rltk::BLACK),
+                        rltk::to_cp437('?'),
+                        200.0,
+                    );
+                }
             }
 
             if can_act {
@@ -82,11 +90,13 @@ impl<'a> System<'a> for MonsterAI {
                             },
                         )
                         .expect("Unable to insert attack");
-                } else if viewshed.visible_tiles.contains(&*player_pos) {
+                } else if viewshed.visible_tiles.contains(&*player_pos)
+                    && distance <= (viewshed.range - stealth) as f32
+                {
                     let path = rltk::a_star_search(
                         map.xy_idx(pos.x, pos.y) as i32,
                         map.xy_idx(player_pos.x, player_pos.y) as i32,
-                        &mut *map,
+                        &*map,
                     );
                     if path.success && path.steps.len() > 1 {
                         let mut idx = map.xy_idx(pos.x, pos.y);
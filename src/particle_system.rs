@@ -93,12 +93,16 @@ impl<'a> System<'a> for ParticleSpawnSystem {
 }
 
 pub fn cull_dead_particle(ecs: &mut World, ctx: &Rltk) {
+    age_particles(ecs, ctx.frame_time_ms);
+}
+
+pub fn age_particles(ecs: &mut World, elapsed_ms: f32) {
     let mut dead_particles: Vec<Entity> = Vec::new();
     {
         let mut particles = ecs.write_storage::<ParticleLifetime>();
         let entities = ecs.entities();
-        for (entity, mut particle) in (&entities, &mut particles).join() {
-            particle.lifetime_ms -= ctx.frame_time_ms;
+        for (entity, particle) in (&entities, &mut particles).join() {
+            particle.lifetime_ms -= elapsed_ms;
             if particle.lifetime_ms < 0.0 {
                 dead_particles.push(entity);
             }
@@ -1,12 +1,37 @@
 use super::{
-    gamelog::GameLog, CombatStats, EntityMoved, HungerClock, HungerState, Item, Map, Monster,
-    Player, Position, RunState, State, TileType, Viewshed, WantsToMelee, WantsToPickupItem,
+    gamelog::{GameLog, LogCategory},
+    CombatStats, EntityMoved, HungerClock, HungerState, Item, Map, Monster, Player, Position,
+    RunState, State, StatusEffects, StatusKind, TileType, Viewshed, WantsToMelee,
+    WantsToPickupItem,
 };
-use rltk::{Point, Rltk, VirtualKeyCode};
-use specs::{prelude::*, storage::GenericWriteStorage};
-use std::cmp::{max, min};
+use rltk::{Point, RandomNumberGenerator, Rltk, VirtualKeyCode};
+use specs::prelude::*;
+
+/// The eight ways a confused player can stumble.
+const STUMBLE_DIRECTIONS: [(i32, i32); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
 
 pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
+    let player_entity = *ecs.fetch::<Entity>();
+    let confused = ecs
+        .read_storage::<StatusEffects>()
+        .get(player_entity)
+        .is_some_and(|effects| effects.has(StatusKind::Confusion));
+    let (delta_x, delta_y) = if confused {
+        let roll = ecs.write_resource::<RandomNumberGenerator>().range(0, 8);
+        STUMBLE_DIRECTIONS[roll as usize]
+    } else {
+        (delta_x, delta_y)
+    };
+
     let mut positions = ecs.write_storage::<Position>();
     let players = ecs.read_storage::<Player>();
     let mut viewsheds = ecs.write_storage::<Viewshed>();
@@ -44,8 +69,8 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
         }
 
         if !map.blocked[destination_idx] {
-            pos.x = min(79, max(0, pos.x + delta_x));
-            pos.y = min(49, max(0, pos.y + delta_y));
+            pos.x = (pos.x + delta_x).clamp(0, map.width - 1);
+            pos.y = (pos.y + delta_y).clamp(0, map.height - 1);
 
             viewshed.dirty = true;
             let mut ppos = ecs.write_resource::<Point>();
@@ -58,7 +83,7 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
     }
 }
 
-fn get_item(ecs: &mut World) {
+pub fn get_item(ecs: &mut World) {
     let player_pos = ecs.fetch::<Point>();
     let player_entity = ecs.fetch::<Entity>();
     let entities = ecs.entities();
@@ -74,9 +99,7 @@ fn get_item(ecs: &mut World) {
     }
 
     match target_item {
-        None => gamelog
-            .entries
-            .push("There is nothing to pick up.".to_string()),
+        None => gamelog.say(LogCategory::Item, "There is nothing to pick up."),
         Some(item) => {
             let mut pickup = ecs.write_storage::<WantsToPickupItem>();
             pickup
@@ -100,12 +123,25 @@ pub fn try_next_level(ecs: &mut World) -> bool {
         true
     } else {
         let mut gamelog = ecs.fetch_mut::<GameLog>();
-        gamelog.entries.push("Can't go down here.".to_string());
+        gamelog.say(LogCategory::System, "Can't go down here.");
+        false
+    }
+}
+
+pub fn try_previous_level(ecs: &mut World) -> bool {
+    let player_pos = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+    let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+    if map.tiles[player_idx] == TileType::UpStairs {
+        true
+    } else {
+        let mut gamelog = ecs.fetch_mut::<GameLog>();
+        gamelog.say(LogCategory::System, "Can't go up here.");
         false
     }
 }
 
-fn skip_turn(ecs: &mut World) -> RunState {
+pub fn skip_turn(ecs: &mut World) -> RunState {
     let player_entity = ecs.fetch::<Entity>();
     let viewshed_components = ecs.read_storage::<Viewshed>();
     let monsters = ecs.read_storage::<Monster>();
@@ -178,6 +214,11 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
                     return RunState::NextLevel;
                 }
             }
+            VirtualKeyCode::Comma => {
+                if try_previous_level(&mut gs.ecs) {
+                    return RunState::PreviousLevel;
+                }
+            }
 
             // Skip Turn
             VirtualKeyCode::Numpad5 => return skip_turn(&mut gs.ecs),
@@ -187,6 +228,8 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
             VirtualKeyCode::I => return RunState::ShowInventory,
             VirtualKeyCode::D => return RunState::ShowDropItem,
             VirtualKeyCode::R => return RunState::ShowRemoveItem,
+            VirtualKeyCode::C => return RunState::CharacterSheet,
+            VirtualKeyCode::M => return RunState::LogHistory { offset: 0 },
 
             VirtualKeyCode::Escape => return RunState::SaveGame,
 
@@ -1,58 +1,69 @@
 use rltk::RandomNumberGenerator;
 
-pub struct RandomEntry {
-    name: String,
+pub struct RandomEntry<T> {
+    key: T,
     weight: i32,
 }
 
-impl RandomEntry {
-    pub fn new<S: ToString>(name: S, weight: i32) -> RandomEntry {
-        RandomEntry {
-            name: name.to_string(),
-            weight,
-        }
+impl<T> RandomEntry<T> {
+    pub fn new(key: T, weight: i32) -> RandomEntry<T> {
+        RandomEntry { key, weight }
     }
 }
 
-#[derive(Default)]
-pub struct RandomTable {
-    entries: Vec<RandomEntry>,
+/// A weighted table: each key is rolled with probability `weight / total_weight`.
+pub struct RandomTable<T> {
+    entries: Vec<RandomEntry<T>>,
     total_weight: i32,
 }
 
-impl RandomTable {
-    pub fn new() -> RandomTable {
+impl<T> Default for RandomTable<T> {
+    fn default() -> RandomTable<T> {
+        RandomTable::new()
+    }
+}
+
+impl<T> RandomTable<T> {
+    pub fn new() -> RandomTable<T> {
         RandomTable {
             entries: Vec::new(),
             total_weight: 0,
         }
     }
 
-    pub fn add<S: ToString>(mut self, name: S, weight: i32) -> RandomTable {
+    /// Adds `key` with the given weight. Entries that can't be rolled (weight 0 or less)
+    /// are left out.
+    pub fn add(mut self, key: T, weight: i32) -> RandomTable<T> {
         if weight > 0 {
             self.total_weight += weight;
-            self.entries
-                .push(RandomEntry::new(name.to_string(), weight));
+            self.entries.push(RandomEntry::new(key, weight));
         }
         self
     }
 
-    pub fn roll(&self, rng: &mut RandomNumberGenerator) -> String {
+    pub fn total_weight(&self) -> i32 {
+        self.total_weight
+    }
+
+    pub fn weights(&self) -> Vec<(&T, i32)> {
+        self.entries.iter().map(|e| (&e.key, e.weight)).collect()
+    }
+
+    /// Picks a key at random, weighted by its share of the total. Returns `None` only when
+    /// the table is empty.
+    pub fn roll(&self, rng: &mut RandomNumberGenerator) -> Option<&T> {
         if self.total_weight == 0 {
-            return "None".to_string();
+            return None;
         }
-        let mut roll = rng.roll_dice(1, self.total_weight) - 1;
-        let mut index: usize = 0;
 
-        while roll > 0 {
-            if roll < self.entries[index].weight {
-                return self.entries[index].name.clone();
+        let mut roll = rng.range(0, self.total_weight);
+        for entry in self.entries.iter() {
+            if roll < entry.weight {
+                return Some(&entry.key);
             }
-
-            roll -= self.entries[index].weight;
-            index += 1;
+            roll -= entry.weight;
         }
 
-        "None".to_string()
+        unreachable!("roll is always below the total weight")
     }
 }
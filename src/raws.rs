@@ -0,0 +1,434 @@
+use super::initiative_system::NORMAL_SPEED;
+use super::{
+    attributes, random_table::RandomTable, AreaOfEffect, Attributes, BlocksTile, CombatStats,
+    Consumable, DamageType, DefenseBonus, Energy, EntryTrigger, EquipmentSlot, Equippable,
+    ExperienceValue, Hidden, Immune, InflictsDamage, InflictsStatus, Item, MeleeWeapon, Monster,
+    Name, Position, ProvidesFood, ProvidesHealing, Ranged, Renderable, Resistant, RevealsMap,
+    SerializeMe, SingleActivation, Skills, StatusEffect, Viewshed, Vulnerable,
+};
+use rltk::RGB;
+use serde::Deserialize;
+use specs::prelude::*;
+use specs::saveload::{MarkedBuilder, SimpleMarker};
+use std::collections::HashMap;
+
+rltk::embedded_resource!(RAW_FILE, "../resources/spawns.json");
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Raws {
+    pub entities: Vec<EntityDef>,
+    pub spawn_table: Vec<SpawnTableEntry>,
+}
+
+/// One spawnable entity, described as the bundle of components it is built from.
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct EntityDef {
+    pub name: String,
+    pub renderable: Option<RenderableDef>,
+    pub viewshed: Option<i32>,
+    #[serde(default)]
+    pub monster: bool,
+    #[serde(default)]
+    pub blocks_tile: bool,
+    /// Anything with attributes can fight; its hit points are derived from them.
+    pub attributes: Option<AttributesDef>,
+    pub skills: Option<SkillsDef>,
+    /// Energy gained per scheduler tick; monsters without one move at normal speed.
+    pub speed: Option<i32>,
+    /// Experience for killing it.
+    pub experience: Option<i32>,
+    #[serde(default)]
+    pub item: bool,
+    #[serde(default)]
+    pub consumable: bool,
+    pub ranged: Option<i32>,
+    pub inflicts_damage: Option<InflictsDamageDef>,
+    pub area_of_effect: Option<i32>,
+    /// A status effect put on whatever the item is used on.
+    pub inflicts_status: Option<StatusEffect>,
+    pub provides_healing: Option<i32>,
+    #[serde(default)]
+    pub provides_food: bool,
+    #[serde(default)]
+    pub reveals_map: bool,
+    pub equippable: Option<EquipmentSlot>,
+    pub melee_weapon: Option<MeleeWeaponDef>,
+    #[serde(default)]
+    pub resistances: Vec<DamageType>,
+    #[serde(default)]
+    pub immunities: Vec<DamageType>,
+    #[serde(default)]
+    pub vulnerabilities: Vec<DamageType>,
+    pub defense_bonus: Option<i32>,
+    #[serde(default)]
+    pub hidden: bool,
+    #[serde(default)]
+    pub entry_trigger: bool,
+    #[serde(default)]
+    pub single_activation: bool,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RenderableDef {
+    pub glyph: String,
+    pub fg: String,
+    pub bg: String,
+    pub order: i32,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+#[serde(default)]
+pub struct AttributesDef {
+    pub might: i32,
+    pub agility: i32,
+    pub toughness: i32,
+    pub intellect: i32,
+}
+
+impl Default for AttributesDef {
+    fn default() -> AttributesDef {
+        let average = Attributes::default();
+        AttributesDef {
+            might: average.might,
+            agility: average.agility,
+            toughness: average.toughness,
+            intellect: average.intellect,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct InflictsDamageDef {
+    pub amount: i32,
+    pub damage_type: DamageType,
+}
+
+/// A weapon's damage dice, like `"1d6+1"`, their type, and its bonus to the attack roll.
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct MeleeWeaponDef {
+    pub damage: String,
+    pub damage_type: DamageType,
+    #[serde(default)]
+    pub hit_bonus: i32,
+}
+
+#[derive(Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+#[serde(default)]
+pub struct SkillsDef {
+    pub melee: i32,
+    pub defense: i32,
+    pub magic: i32,
+    pub stealth: i32,
+}
+
+/// A spawn table row. Its weight at a given depth is `weight + weight_per_depth * depth`,
+/// and it is left out of the table outside `min_depth..=max_depth`.
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SpawnTableEntry {
+    pub name: String,
+    pub weight: i32,
+    #[serde(default)]
+    pub weight_per_depth: i32,
+    #[serde(default = "default_min_depth")]
+    pub min_depth: i32,
+    pub max_depth: Option<i32>,
+}
+
+fn default_min_depth() -> i32 {
+    1
+}
+
+impl SpawnTableEntry {
+    fn ever_spawns(&self) -> bool {
+        if let Some(max_depth) = self.max_depth {
+            if max_depth < self.min_depth {
+                return false;
+            }
+        }
+        // Weight is linear in depth, so it peaks at one end of the range
+        let best_depth = if self.weight_per_depth > 0 {
+            match self.max_depth {
+                None => return true,
+                Some(max_depth) => max_depth,
+            }
+        } else {
+            self.min_depth
+        };
+        self.weight + self.weight_per_depth * best_depth > 0
+    }
+}
+
+/// A handle to one of the loaded entity definitions. Keys only come from the `RawMaster`
+/// that issued them, so spawning by key cannot miss.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub struct EntityKey(usize);
+
+pub struct RawMaster {
+    raws: Raws,
+    entity_index: HashMap<String, usize>,
+}
+
+impl RawMaster {
+    /// Loads the raws bundled into the binary. They ship with the game, so a broken file is
+    /// a build problem and fails loudly at startup.
+    pub fn load() -> RawMaster {
+        rltk::link_resource!(RAW_FILE, "../resources/spawns.json");
+
+        let raw_data = rltk::embedding::EMBED
+            .lock()
+            .get_resource("../resources/spawns.json".to_string())
+            .unwrap();
+        let raw_string = std::str::from_utf8(raw_data).expect("Unable to read spawns.json");
+        let raws =
+            RawMaster::from_json(raw_string).unwrap_or_else(|e| panic!("spawns.json: {}", e));
+        for name in raws.unreachable() {
+            rltk::console::log(format!(
+                "WARNING: spawns.json: {} can never be rolled from the spawn table",
+                name
+            ));
+        }
+        raws
+    }
+
+    pub fn from_json(json: &str) -> Result<RawMaster, String> {
+        let raws: Raws = serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+        let mut entity_index = HashMap::new();
+        for (i, entity) in raws.entities.iter().enumerate() {
+            if let Some(renderable) = &entity.renderable {
+                build_renderable(renderable).map_err(|e| format!("{}: {}", entity.name, e))?;
+            }
+            if let Some(weapon) = &entity.melee_weapon {
+                build_melee_weapon(weapon).map_err(|e| format!("{}: {}", entity.name, e))?;
+            }
+            if entity.skills.is_some() && entity.attributes.is_none() {
+                return Err(format!("{} has skills but no attributes", entity.name));
+            }
+            if entity
+                .inflicts_status
+                .is_some_and(|effect| effect.turns < 1)
+            {
+                return Err(format!(
+                    "{} inflicts a status that never lasts",
+                    entity.name
+                ));
+            }
+            if entity_index.insert(entity.name.clone(), i).is_some() {
+                return Err(format!("{} is defined more than once", entity.name));
+            }
+        }
+
+        for entry in raws.spawn_table.iter() {
+            if !entity_index.contains_key(&entry.name) {
+                return Err(format!(
+                    "spawn table entry {} does not match any entity",
+                    entry.name
+                ));
+            }
+        }
+
+        Ok(RawMaster { raws, entity_index })
+    }
+
+    /// Names of entities that no spawn table row can ever roll, either because they have no
+    /// row or because the row's weight never rises above zero within its depth range.
+    pub fn unreachable(&self) -> Vec<&str> {
+        self.raws
+            .entities
+            .iter()
+            .filter(|entity| {
+                !self
+                    .raws
+                    .spawn_table
+                    .iter()
+                    .any(|entry| entry.name == entity.name && entry.ever_spawns())
+            })
+            .map(|entity| entity.name.as_str())
+            .collect()
+    }
+
+    pub fn key(&self, name: &str) -> Option<EntityKey> {
+        self.entity_index.get(name).map(|i| EntityKey(*i))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&EntityDef> {
+        self.key(name).map(|key| self.def(key))
+    }
+
+    pub fn def(&self, key: EntityKey) -> &EntityDef {
+        &self.raws.entities[key.0]
+    }
+
+    pub fn spawn_table(&self, depth: i32) -> RandomTable<EntityKey> {
+        let mut table = RandomTable::new();
+        for entry in self.raws.spawn_table.iter() {
+            let in_range = depth >= entry.min_depth
+                && entry.max_depth.is_none_or(|max_depth| depth <= max_depth);
+            if in_range {
+                table = table.add(
+                    EntityKey(self.entity_index[&entry.name]),
+                    entry.weight + entry.weight_per_depth * depth,
+                );
+            }
+        }
+        table
+    }
+}
+
+fn build_renderable(def: &RenderableDef) -> Result<Renderable, String> {
+    let mut chars = def.glyph.chars();
+    let glyph = match (chars.next(), chars.next()) {
+        (Some(c), None) => rltk::to_cp437(c),
+        _ => return Err(format!("glyph {:?} must be a single character", def.glyph)),
+    };
+    let fg = RGB::from_hex(&def.fg).map_err(|_| format!("invalid fg colour {}", def.fg))?;
+    let bg = RGB::from_hex(&def.bg).map_err(|_| format!("invalid bg colour {}", def.bg))?;
+
+    Ok(Renderable {
+        glyph,
+        fg,
+        bg,
+        render_order: def.order,
+    })
+}
+
+fn build_melee_weapon(def: &MeleeWeaponDef) -> Result<MeleeWeapon, String> {
+    let damage = rltk::parse_dice_string(&def.damage)
+        .ok()
+        .filter(|dice| dice.die_type > 0)
+        .ok_or_else(|| format!("invalid damage dice {}", def.damage))?;
+    Ok(MeleeWeapon {
+        damage,
+        damage_type: def.damage_type,
+        hit_bonus: def.hit_bonus,
+    })
+}
+
+/// Builds the entity called `name` at (x, y), or returns `None` if the raws don't define it.
+pub fn spawn_named(ecs: &mut World, name: &str, x: i32, y: i32) -> Option<Entity> {
+    let key = ecs.fetch::<RawMaster>().key(name)?;
+    Some(spawn_entity(ecs, key, x, y))
+}
+
+pub fn spawn_entity(ecs: &mut World, key: EntityKey, x: i32, y: i32) -> Entity {
+    let def = ecs.fetch::<RawMaster>().def(key).clone();
+
+    let mut eb = ecs.create_entity().with(Position { x, y }).with(Name {
+        name: def.name.clone(),
+    });
+
+    if let Some(renderable) = &def.renderable {
+        eb = eb.with(build_renderable(renderable).expect("Renderable checked at load"));
+    }
+    if let Some(range) = def.viewshed {
+        eb = eb.with(Viewshed {
+            visible_tiles: Vec::new(),
+            range,
+            dirty: true,
+        });
+    }
+    if def.monster {
+        eb = eb.with(Monster {});
+    }
+    if let Some(speed) = def.speed.or_else(|| def.monster.then_some(NORMAL_SPEED)) {
+        eb = eb.with(Energy { speed, energy: 0 });
+    }
+    if let Some(xp) = def.experience {
+        eb = eb.with(ExperienceValue { xp });
+    }
+    if def.blocks_tile {
+        eb = eb.with(BlocksTile {});
+    }
+    if let Some(def_attributes) = &def.attributes {
+        let attributes = Attributes {
+            might: def_attributes.might,
+            agility: def_attributes.agility,
+            toughness: def_attributes.toughness,
+            intellect: def_attributes.intellect,
+        };
+        let def_skills = def.skills.clone().unwrap_or_default();
+        let max_hp = attributes::max_hp(&attributes, 1);
+        eb = eb
+            .with(attributes)
+            .with(Skills {
+                melee: def_skills.melee,
+                defense: def_skills.defense,
+                magic: def_skills.magic,
+                stealth: def_skills.stealth,
+            })
+            .with(CombatStats { max_hp, hp: max_hp });
+    }
+    if def.item {
+        eb = eb.with(Item {});
+    }
+    if def.consumable {
+        eb = eb.with(Consumable {});
+    }
+    if let Some(range) = def.ranged {
+        eb = eb.with(Ranged { range });
+    }
+    if let Some(damage) = &def.inflicts_damage {
+        eb = eb.with(InflictsDamage {
+            damage: damage.amount,
+            damage_type: damage.damage_type,
+        });
+    }
+    if let Some(radius) = def.area_of_effect {
+        eb = eb.with(AreaOfEffect { radius });
+    }
+    if let Some(effect) = def.inflicts_status {
+        eb = eb.with(InflictsStatus { effect });
+    }
+    if let Some(heal_amount) = def.provides_healing {
+        eb = eb.with(ProvidesHealing { heal_amount });
+    }
+    if def.provides_food {
+        eb = eb.with(ProvidesFood {});
+    }
+    if def.reveals_map {
+        eb = eb.with(RevealsMap {});
+    }
+    if let Some(slot) = def.equippable {
+        eb = eb.with(Equippable { slot });
+    }
+    if let Some(weapon) = &def.melee_weapon {
+        eb = eb.with(build_melee_weapon(weapon).expect("Weapon checked at load"));
+    }
+    if let Some(defense) = def.defense_bonus {
+        eb = eb.with(DefenseBonus { defense });
+    }
+    if !def.resistances.is_empty() {
+        eb = eb.with(Resistant {
+            damage_types: def.resistances.clone(),
+        });
+    }
+    if !def.immunities.is_empty() {
+        eb = eb.with(Immune {
+            damage_types: def.immunities.clone(),
+        });
+    }
+    if !def.vulnerabilities.is_empty() {
+        eb = eb.with(Vulnerable {
+            damage_types: def.vulnerabilities.clone(),
+        });
+    }
+    if def.hidden {
+        eb = eb.with(Hidden {});
+    }
+    if def.entry_trigger {
+        eb = eb.with(EntryTrigger {});
+    }
+    if def.single_activation {
+        eb = eb.with(SingleActivation {});
+    }
+
+    eb.marked::<SimpleMarker<SerializeMe>>().build()
+}
@@ -1,113 +1,970 @@
 use super::components::*;
+use super::dungeon::MasterDungeonMap;
+use super::game_clock::GameClock;
+use super::initiative_system::NORMAL_SPEED;
+use super::map::rle_bits;
+use super::RunSeed;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use specs::error::NoError;
 use specs::prelude::*;
 use specs::saveload::{
     DeserializeComponents, MarkedBuilder, SerializeComponents, SimpleMarker, SimpleMarkerAllocator,
 };
+use std::fmt;
 use std::fs;
-use std::fs::File;
-use std::path::Path;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Where saves went before there were slots. Found saves are moved into a free slot.
+const LEGACY_SAVE_PATH: &str = "./savegame.json";
+
+pub const SAVE_SLOT_COUNT: usize = 5;
+
+/// How often the game saves itself, in player turns.
+pub const AUTOSAVE_INTERVAL: u32 = 100;
+
+/// Binary saves start with this, so they can't be mistaken for JSON ones.
+const BINARY_MAGIC: &[u8] = b"HRLTKSAV";
+
+/// Version of the saved component data. Bump it whenever a saved component changes shape and
+/// add a step to `MIGRATIONS` that upgrades the previous version.
+///
+/// 1. The original single-level game.
+/// 2. Levels are kept: `OtherLevelPosition`, and the dungeon in `SerializationHelper`.
+/// 3. The turn count in `SerializationHelper`.
+/// 4. Run-length encoded map bit vectors.
+/// 5. The game clock, turns and ticks, replaces the turn count.
+/// 6. `Energy` and `MyTurn` for the turn schedule.
+/// 7. Experience, and the source of each hit in `SufferDamage`.
+/// 8. `Attributes` and `Skills` replace power and defense in `CombatStats`.
+/// 9. `MeleeWeapon` damage dice replace `MeleePowerBonus`.
+/// 10. Damage types, and `Resistant`, `Immune` and `Vulnerable`.
+/// 11. `StatusEffects` and `InflictsStatus` replace `Confusion`.
+/// 12. The run's seed in `SerializationHelper`.
+pub const SCHEMA_VERSION: u32 = 12;
+
+/// Saves from before the header was added are a bare stream of storages in this order.
+/// Schema 2 saves of that kind also have `OtherLevelPosition` right after `Position`.
+pub const LEGACY_STORAGES: &[&str] = &[
+    "Position",
+    "Renderable",
+    "Player",
+    "Viewshed",
+    "Monster",
+    "Name",
+    "BlocksTile",
+    "CombatStats",
+    "SufferDamage",
+    "WantsToMelee",
+    "Item",
+    "Consumable",
+    "Ranged",
+    "InflictsDamage",
+    "AreaOfEffect",
+    "Confusion",
+    "ProvidesHealing",
+    "ProvidesFood",
+    "RevealsMap",
+    "MeleePowerBonus",
+    "DefenseBonus",
+    "InBackpack",
+    "Equippable",
+    "Equipped",
+    "WantsToPickupItem",
+    "WantsToUseItem",
+    "WantsToDropItem",
+    "WantsToRemoveItem",
+    "HungerClock",
+    "Hidden",
+    "EntryTrigger",
+    "EntityMoved",
+    "SingleActivation",
+    "SerializationHelper",
+];
+
+type Storages = serde_json::Map<String, Value>;
+type Migration = fn(&mut Storages) -> Result<(), SaveError>;
+
+/// `MIGRATIONS[n]` upgrades a save from schema `n + 1` to schema `n + 2`.
+const MIGRATIONS: &[Migration] = &[
+    add_dungeon,
+    add_turn_count,
+    encode_map_bits,
+    add_game_clock,
+    add_energy,
+    add_experience,
+    add_attributes,
+    add_weapon_dice,
+    add_damage_types,
+    add_status_effects,
+    add_run_seed,
+];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SaveHeader {
+    pub game_version: String,
+    pub schema_version: u32,
+}
+
+/// What the slot picker shows about a save, so it doesn't have to load it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SlotSummary {
+    pub name: String,
+    pub depth: i32,
+    pub hp: i32,
+    pub max_hp: i32,
+    pub turn: u32,
+    /// Seconds since the Unix epoch.
+    pub saved_at: u64,
+    /// Written by the game rather than the player. Finding one at startup means the last
+    /// run ended without the player saving and quitting, most likely in a crash.
+    #[serde(default)]
+    pub autosave: bool,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum SaveKind {
+    /// The player saved and quit.
+    Manual,
+    Autosave,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SaveFile {
+    header: SaveHeader,
+    #[serde(default)]
+    summary: Option<SlotSummary>,
+    storages: Storages,
+}
+
+/// The part of a save the slot picker reads.
+#[derive(Deserialize)]
+struct SaveFileHead {
+    #[serde(default)]
+    summary: Option<SlotSummary>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SlotStatus {
+    Empty,
+    /// A save without a summary was written before slots existed.
+    Saved(Option<SlotSummary>),
+}
+
+/// How a save is written. Loading works out which one it is by itself.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum SaveFormat {
+    /// MessagePack, the default.
+    Binary,
+    /// Human readable, for debugging.
+    Json,
+}
+
+impl SaveFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            SaveFormat::Binary => "Binary",
+            SaveFormat::Json => "JSON (debug)",
+        }
+    }
+}
+
+/// The save slots, kept in the platform's data directory. `current` is the slot the running
+/// game was loaded from or last saved to; `format` is what the next save is written as.
+#[derive(Clone)]
+pub struct SaveSlots {
+    dir: PathBuf,
+    pub current: Option<usize>,
+    pub format: SaveFormat,
+    pub autosave: bool,
+}
+
+impl SaveSlots {
+    pub fn in_data_dir() -> SaveSlots {
+        let dir = directories::ProjectDirs::from("", "", "hello-rltk")
+            .map(|dirs| dirs.data_dir().join("saves"))
+            .unwrap_or_else(|| PathBuf::from("./saves"));
+        SaveSlots::in_dir(dir)
+    }
+
+    pub fn in_dir<P: Into<PathBuf>>(dir: P) -> SaveSlots {
+        SaveSlots {
+            dir: dir.into(),
+            current: None,
+            format: SaveFormat::Binary,
+            autosave: true,
+        }
+    }
+
+    pub fn path(&self, slot: usize) -> PathBuf {
+        self.dir.join(format!("slot{}.sav", slot + 1))
+    }
+
+    pub fn is_used(&self, slot: usize) -> bool {
+        self.path(slot).exists()
+    }
+
+    pub fn any_used(&self) -> bool {
+        (0..SAVE_SLOT_COUNT).any(|slot| self.is_used(slot))
+    }
+
+    pub fn first_free(&self) -> Option<usize> {
+        (0..SAVE_SLOT_COUNT).find(|slot| !self.is_used(*slot))
+    }
+
+    pub fn list(&self) -> Vec<SlotStatus> {
+        (0..SAVE_SLOT_COUNT)
+            .map(|slot| match fs::read(self.path(slot)) {
+                Err(_) => SlotStatus::Empty,
+                Ok(data) => SlotStatus::Saved(
+                    decode::<SaveFileHead>(&data)
+                        .ok()
+                        .and_then(|head| head.summary),
+                ),
+            })
+            .collect()
+    }
+
+    /// The slot an interrupted run autosaved into, if there is one.
+    pub fn find_autosave(&self) -> Option<(usize, SlotSummary)> {
+        self.list()
+            .into_iter()
+            .enumerate()
+            .find_map(|(slot, status)| match status {
+                SlotStatus::Saved(Some(summary)) if summary.autosave => Some((slot, summary)),
+                _ => None,
+            })
+    }
+
+    /// Where the running game autosaves: its own slot, or the first free one.
+    pub fn autosave_slot(&self) -> Option<usize> {
+        self.current.or_else(|| self.first_free())
+    }
+
+    pub fn delete(&self, slot: usize) -> Result<(), SaveError> {
+        let path = self.path(slot);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Moves a save from before slots existed into the first free slot, and gives slots
+    /// from before binary saves their current file name.
+    pub fn adopt_legacy_save(&self) -> Result<(), SaveError> {
+        for slot in 0..SAVE_SLOT_COUNT {
+            let json_slot = self.dir.join(format!("slot{}.json", slot + 1));
+            if json_slot.exists() && !self.is_used(slot) {
+                fs::rename(json_slot, self.path(slot))?;
+            }
+        }
+        if !Path::new(LEGACY_SAVE_PATH).exists() {
+            return Ok(());
+        }
+        if let Some(slot) = self.first_free() {
+            fs::create_dir_all(&self.dir)?;
+            fs::copy(LEGACY_SAVE_PATH, self.path(slot))?;
+            fs::remove_file(LEGACY_SAVE_PATH)?;
+        }
+        Ok(())
+    }
+}
+
+/// `saved_at` as a UTC date and time, e.g. "2021-03-04 17:05".
+pub fn format_timestamp(saved_at: u64) -> String {
+    let days = (saved_at / 86_400) as i64;
+    let minutes_today = (saved_at % 86_400) / 60;
+
+    // Civil date from days since the epoch, after Howard Hinnant's `civil_from_days`.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        minutes_today / 60,
+        minutes_today % 60
+    )
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Binary(String),
+    /// The save was written by a newer build than this one.
+    TooNew(SaveHeader),
+    /// The save parsed, but doesn't hold a game we can rebuild.
+    Corrupt(String),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "couldn't access the save file ({})", err),
+            SaveError::Json(err) => write!(f, "the save file is unreadable ({})", err),
+            SaveError::Binary(err) => write!(f, "the save file is unreadable ({})", err),
+            SaveError::TooNew(header) => write!(
+                f,
+                "the save is from version {} (save format {}), this is {} (save format {})",
+                header.game_version,
+                header.schema_version,
+                env!("CARGO_PKG_VERSION"),
+                SCHEMA_VERSION
+            ),
+            SaveError::Corrupt(reason) => write!(f, "the save file is damaged ({})", reason),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<std::io::Error> for SaveError {
+    fn from(err: std::io::Error) -> SaveError {
+        SaveError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(err: serde_json::Error) -> SaveError {
+        SaveError::Json(err)
+    }
+}
+
+impl From<rmp_serde::encode::Error> for SaveError {
+    fn from(err: rmp_serde::encode::Error) -> SaveError {
+        SaveError::Binary(err.to_string())
+    }
+}
+
+impl From<rmp_serde::decode::Error> for SaveError {
+    fn from(err: rmp_serde::decode::Error) -> SaveError {
+        SaveError::Binary(err.to_string())
+    }
+}
+
+/// Shown on the main menu after a load fails, until a new game is started.
+pub struct LoadFailure {
+    pub message: String,
+}
 
 macro_rules! serialize_individually {
-    ($ecs:expr, $ser:expr, $data:expr, $( $type:ty), *) => {
+    ($ecs:expr, $storages:expr, $data:expr, $( $type:ty), *) => {
         $(
-        SerializeComponents::<NoError, SimpleMarker<SerializeMe>>::serialize(
-            &($ecs.read_storage::<$type>(), ),
-            &$data.0,
-            &$data.1,
-            &mut $ser,
-        )
-        .unwrap();
+        $storages.insert(
+            stringify!($type).to_string(),
+            SerializeComponents::<NoError, SimpleMarker<SerializeMe>>::serialize(
+                &($ecs.read_storage::<$type>(), ),
+                &$data.0,
+                &$data.1,
+                serde_json::value::Serializer,
+            )?,
+        );
         )*
     };
 }
 
 macro_rules! deserialize_individually {
-    ($ecs:expr, $de:expr, $data:expr, $( $type:ty),*) => {
+    ($ecs:expr, $storages:expr, $data:expr, $( $type:ty),*) => {
         $(
         DeserializeComponents::<NoError, _>::deserialize(
             &mut ( &mut $ecs.write_storage::<$type>(), ),
             &$data.0, // entities
             &mut $data.1, // marker
             &mut $data.2, // allocater
-            &mut $de,
+            $storages.remove(stringify!($type)).unwrap_or_else(|| Value::Array(Vec::new())),
         )
-        .unwrap();
+        .map_err(|err| SaveError::Corrupt(format!("{}: {}", stringify!($type), err)))?;
         )*
     };
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-pub fn save_game(ecs: &mut World) {
-    // Create player
+/// The whole game as a versioned JSON save document.
+pub fn save_to_string(ecs: &mut World) -> Result<String, SaveError> {
+    Ok(serde_json::to_string(&build_save(ecs)?)?)
+}
+
+/// The whole game as a save file in `format`.
+pub fn save_to_bytes(ecs: &mut World, format: SaveFormat) -> Result<Vec<u8>, SaveError> {
+    encode(&build_save(ecs)?, format)
+}
+
+fn encode(save: &SaveFile, format: SaveFormat) -> Result<Vec<u8>, SaveError> {
+    match format {
+        SaveFormat::Json => Ok(serde_json::to_vec(&save)?),
+        SaveFormat::Binary => {
+            let mut bytes = BINARY_MAGIC.to_vec();
+            bytes.extend(rmp_serde::to_vec_named(save)?);
+            Ok(bytes)
+        }
+    }
+}
+
+fn build_save(ecs: &mut World) -> Result<SaveFile, SaveError> {
     let mapcopy = ecs.get_mut::<super::map::Map>().unwrap().clone();
+    let dungeon = (*ecs.fetch::<MasterDungeonMap>()).clone();
+    let clock = *ecs.fetch::<GameClock>();
+    let seed = ecs.fetch::<RunSeed>().seed;
+    let summary = summarize(ecs);
     let savehelper = ecs
         .create_entity()
-        .with(SerializationHelper { map: mapcopy })
+        .with(SerializationHelper {
+            map: mapcopy,
+            dungeon,
+            clock,
+            seed,
+        })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 
-    // Serializing
-    {
-        let data = (
-            ecs.entities(),
-            ecs.read_storage::<SimpleMarker<SerializeMe>>(),
-        );
+    let serialized = serialize_storages(ecs);
+    ecs.delete_entity(savehelper).expect("Crash on cleanup");
 
-        let writer = File::create("./savegame.json").unwrap();
-        let mut serializer = serde_json::Serializer::new(writer);
-        serialize_individually!(
-            ecs,
-            serializer,
-            data,
-            Position,
-            Renderable,
-            Player,
-            Viewshed,
-            Monster,
-            Name,
-            BlocksTile,
-            CombatStats,
-            SufferDamage,
-            WantsToMelee,
-            Item,
-            Consumable,
-            Ranged,
-            InflictsDamage,
-            AreaOfEffect,
-            Confusion,
-            ProvidesHealing,
-            ProvidesFood,
-            RevealsMap,
-            MeleePowerBonus,
-            DefenseBonus,
-            InBackpack,
-            Equippable,
-            Equipped,
-            WantsToPickupItem,
-            WantsToUseItem,
-            WantsToDropItem,
-            WantsToRemoveItem,
-            HungerClock,
-            Hidden,
-            EntryTrigger,
-            EntityMoved,
-            SingleActivation,
-            SerializationHelper
-        );
+    Ok(SaveFile {
+        header: SaveHeader {
+            game_version: env!("CARGO_PKG_VERSION").to_string(),
+            schema_version: SCHEMA_VERSION,
+        },
+        summary: Some(summary),
+        storages: serialized?,
+    })
+}
+
+fn summarize(ecs: &World) -> SlotSummary {
+    let player = *ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Name>();
+    let stats = ecs.read_storage::<CombatStats>();
+    let (hp, max_hp) = stats
+        .get(player)
+        .map_or((0, 0), |stats| (stats.hp, stats.max_hp));
+    SlotSummary {
+        name: names
+            .get(player)
+            .map_or_else(String::new, |name| name.name.clone()),
+        depth: ecs.fetch::<super::map::Map>().depth,
+        hp,
+        max_hp,
+        turn: ecs.fetch::<GameClock>().turn,
+        saved_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs()),
+        autosave: false,
     }
+}
 
-    ecs.delete_entity(savehelper).expect("Crash on cleanup");
+fn serialize_storages(ecs: &World) -> Result<Storages, SaveError> {
+    let data = (
+        ecs.entities(),
+        ecs.read_storage::<SimpleMarker<SerializeMe>>(),
+    );
+    let mut storages = Storages::new();
+    with_all_components!(serialize_individually, ecs, storages, data);
+    Ok(storages)
+}
+
+/// Saves the game into `slot`, which becomes the run's current slot. A character only has
+/// one save, so if the run was saved elsewhere before, that save is removed.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_game(ecs: &mut World, slot: usize, kind: SaveKind) -> Result<(), SaveError> {
+    let format = ecs.fetch::<SaveSlots>().format;
+    let mut save = build_save(ecs)?;
+    if let Some(summary) = save.summary.as_mut() {
+        summary.autosave = kind == SaveKind::Autosave;
+    }
+    let save = encode(&save, format)?;
+
+    let mut slots = ecs.write_resource::<SaveSlots>();
+    fs::create_dir_all(&slots.dir)?;
+    write_atomically(&slots.path(slot), &save)?;
+    if let Some(previous) = slots.current.replace(slot) {
+        if previous != slot {
+            slots.delete(previous)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the whole file next to `path` and only then renames it into place, so a crash
+/// part way through leaves the previous save intact.
+#[cfg(not(target_arch = "wasm32"))]
+fn write_atomically(path: &Path, data: &[u8]) -> Result<(), SaveError> {
+    let partial = path.with_extension("partial");
+    {
+        let mut file = fs::File::create(&partial)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+    }
+    fs::rename(&partial, path)?;
+    Ok(())
 }
 
 #[cfg(target_arch = "wasm32")]
-pub fn save_game(_ecs: &mut World) {}
+pub fn save_game(_ecs: &mut World, _slot: usize, _kind: SaveKind) -> Result<(), SaveError> {
+    Ok(())
+}
+
+/// Removes the running character's save, if it has one. Called when the character dies, so
+/// an autosave can't bring them back.
+pub fn delete_save(ecs: &World) -> Result<(), SaveError> {
+    let mut slots = ecs.write_resource::<SaveSlots>();
+    match slots.current.take() {
+        None => Ok(()),
+        Some(slot) => slots.delete(slot),
+    }
+}
+
+/// Reads a save's header without loading it. Saves from before headers existed report the
+/// schema they were upgraded from and an unknown game version.
+pub fn read_header(data: &[u8]) -> Result<SaveHeader, SaveError> {
+    Ok(parse_save(data)?.header)
+}
+
+/// Decodes a binary or JSON save, or the start of one.
+fn decode<T: serde::de::DeserializeOwned>(data: &[u8]) -> Result<T, SaveError> {
+    match data.strip_prefix(BINARY_MAGIC) {
+        Some(binary) => Ok(rmp_serde::from_slice(binary)?),
+        None => Ok(serde_json::from_slice(data)?),
+    }
+}
+
+/// Parses a save of any supported age. It still has to be migrated to the current schema.
+fn parse_save(data: &[u8]) -> Result<SaveFile, SaveError> {
+    if data.starts_with(BINARY_MAGIC) {
+        return decode(data);
+    }
+
+    let mut values = serde_json::Deserializer::from_slice(data)
+        .into_iter::<Value>()
+        .collect::<Result<Vec<_>, _>>()?;
 
-pub fn does_save_exist() -> bool {
-    Path::new("./savegame.json").exists()
+    let headered = values.len() == 1
+        && values[0]
+            .as_object()
+            .is_some_and(|doc| doc.contains_key("header"));
+    if headered {
+        Ok(serde_json::from_value(values.remove(0))?)
+    } else {
+        parse_legacy_stream(values)
+    }
+}
+
+fn parse_legacy_stream(values: Vec<Value>) -> Result<SaveFile, SaveError> {
+    let mut names = LEGACY_STORAGES.to_vec();
+    let schema_version = if values.len() == names.len() {
+        1
+    } else if values.len() == names.len() + 1 {
+        names.insert(1, "OtherLevelPosition");
+        2
+    } else {
+        return Err(SaveError::Corrupt(format!(
+            "expected {} component lists, found {}",
+            names.len(),
+            values.len()
+        )));
+    };
+    Ok(SaveFile {
+        header: SaveHeader {
+            game_version: "unknown".to_string(),
+            schema_version,
+        },
+        summary: None,
+        storages: names.into_iter().map(str::to_string).zip(values).collect(),
+    })
+}
+
+fn migrate(save: &mut SaveFile) -> Result<(), SaveError> {
+    let version = save.header.schema_version;
+    if version > SCHEMA_VERSION {
+        return Err(SaveError::TooNew(save.header.clone()));
+    }
+    if version == 0 {
+        return Err(SaveError::Corrupt("schema version 0".to_string()));
+    }
+    for upgrade in &MIGRATIONS[version as usize - 1..] {
+        upgrade(&mut save.storages)?;
+    }
+    save.header.schema_version = SCHEMA_VERSION;
+    Ok(())
+}
+
+/// Schema 1 -> 2: single-level saves get an empty dungeon. The current level is stored in it
+/// as soon as the player takes the stairs.
+fn add_dungeon(storages: &mut Storages) -> Result<(), SaveError> {
+    for helper in storage_components(storages, "SerializationHelper")? {
+        if let Some(helper) = helper.as_object_mut() {
+            helper
+                .entry("dungeon")
+                .or_insert_with(|| serde_json::to_value(MasterDungeonMap::default()).unwrap());
+        }
+    }
+    Ok(())
+}
+
+/// Schema 2 -> 3: saves from before turns were counted start from turn 0.
+fn add_turn_count(storages: &mut Storages) -> Result<(), SaveError> {
+    for helper in storage_components(storages, "SerializationHelper")? {
+        if let Some(helper) = helper.as_object_mut() {
+            helper.entry("turns").or_insert_with(|| Value::from(0));
+        }
+    }
+    Ok(())
+}
+
+/// Schema 3 -> 4: the map bit vectors, in the current map and every stored level, become
+/// run lengths.
+fn encode_map_bits(storages: &mut Storages) -> Result<(), SaveError> {
+    for helper in storage_components(storages, "SerializationHelper")? {
+        let helper = match helper.as_object_mut() {
+            None => continue,
+            Some(helper) => helper,
+        };
+        if let Some(map) = helper.get_mut("map") {
+            encode_bits_in_map(map)?;
+        }
+        let stored = helper
+            .get_mut("dungeon")
+            .and_then(|dungeon| dungeon.get_mut("maps"))
+            .and_then(Value::as_object_mut);
+        for map in stored.into_iter().flat_map(|maps| maps.values_mut()) {
+            encode_bits_in_map(map)?;
+        }
+    }
+    Ok(())
+}
+
+fn encode_bits_in_map(map: &mut Value) -> Result<(), SaveError> {
+    for field in &["revealed_tiles", "visible_tiles", "blocked"] {
+        if let Some(bits) = map.get_mut(*field) {
+            let decoded: Vec<bool> = serde_json::from_value(bits.take())?;
+            *bits = serde_json::to_value(rle_bits::encode(&decoded))?;
+        }
+    }
+    Ok(())
+}
+
+/// Schema 4 -> 5: the turn count becomes the game clock. Ticks weren't counted before, so
+/// the clock's ticks start from zero.
+fn add_game_clock(storages: &mut Storages) -> Result<(), SaveError> {
+    for helper in storage_components(storages, "SerializationHelper")? {
+        if let Some(helper) = helper.as_object_mut() {
+            let turn = helper.remove("turns").unwrap_or_else(|| Value::from(0));
+            helper
+                .entry("clock")
+                .or_insert_with(|| serde_json::json!({ "turn": turn, "tick": 0 }));
+        }
+    }
+    Ok(())
+}
+
+/// Schema 5 -> 6: before the turn schedule everything acted once a turn, so the player and
+/// the monsters join it at normal speed.
+fn add_energy(storages: &mut Storages) -> Result<(), SaveError> {
+    let energy = Energy {
+        speed: NORMAL_SPEED,
+        energy: 0,
+    };
+    give_component(storages, &["Player", "Monster"], "Energy", energy)
+}
+
+/// Schema 6 -> 7: pending damage records who dealt it, which older saves never knew, and
+/// the player starts gaining experience from level 1.
+fn add_experience(storages: &mut Storages) -> Result<(), SaveError> {
+    for damage in storage_components(storages, "SufferDamage")? {
+        if let Some(amounts) = damage.get_mut("amount").and_then(Value::as_array_mut) {
+            for amount in amounts.iter_mut() {
+                if amount.is_number() {
+                    *amount = serde_json::json!({ "amount": amount.take(), "source": null });
+                }
+            }
+        }
+    }
+    let experience = Experience { level: 1, xp: 0 };
+    give_component(storages, &["Player"], "Experience", experience)
+}
+
+/// Schema 7 -> 8: power and defense are derived from attributes and skills. Fighters get
+/// average attributes, which add nothing, and their old power and defense as melee and
+/// defense skill, so they hit and dodge as hard as before.
+fn add_attributes(storages: &mut Storages) -> Result<(), SaveError> {
+    let corrupt = || SaveError::Corrupt("CombatStats isn't a component list".to_string());
+    let saved = match storages.get_mut("CombatStats") {
+        None => return Ok(()),
+        Some(saved) => saved.as_array_mut().ok_or_else(corrupt)?,
+    };
+    let average = serde_json::to_value(Attributes::default())?;
+    let mut attributes = Vec::new();
+    let mut skills = Vec::new();
+    for entry in saved.iter_mut() {
+        let marker = entry["marker"].clone();
+        let stats = match entry
+            .get_mut("components")
+            .and_then(|components| components.get_mut(0))
+            .and_then(Value::as_object_mut)
+        {
+            None => continue,
+            Some(stats) => stats,
+        };
+        let power = stats.remove("power").unwrap_or_else(|| Value::from(0));
+        let defense = stats.remove("defense").unwrap_or_else(|| Value::from(0));
+        attributes.push(serde_json::json!({
+            "marker": marker.clone(),
+            "components": [average.clone()],
+        }));
+        skills.push(serde_json::json!({
+            "marker": marker,
+            "components": [{ "melee": power, "defense": defense, "magic": 0, "stealth": 0 }],
+        }));
+    }
+    storages
+        .entry("Attributes")
+        .or_insert_with(|| Value::Array(attributes));
+    storages
+        .entry("Skills")
+        .or_insert_with(|| Value::Array(skills));
+    Ok(())
 }
 
-pub fn load_game(ecs: &mut World) {
+/// Schema 8 -> 9: weapons roll damage dice instead of adding flat power. Each one becomes
+/// a single die with the same average, `1d(2p-1)` for power `p`.
+fn add_weapon_dice(storages: &mut Storages) -> Result<(), SaveError> {
+    for weapon in storage_components(storages, "MeleePowerBonus")? {
+        if let Some(power) = weapon.get("power").and_then(Value::as_i64) {
+            let power = power as i32;
+            let die_type = (2 * power - 1).max(1);
+            let damage = rltk::DiceType::new(1, die_type, power - (die_type + 1) / 2);
+            *weapon = serde_json::json!({ "damage": damage, "hit_bonus": 0 });
+        }
+    }
+    if let Some(weapons) = storages.remove("MeleePowerBonus") {
+        storages.entry("MeleeWeapon").or_insert(weapons);
+    }
+    Ok(())
+}
+
+/// Schema 9 -> 10: damage has a type. Hits still pending stay untyped. Weapons were all
+/// blades, so they slash; damaging items burn if they blast an area, smack if they're
+/// traps, and pierce otherwise, as the raws have them.
+fn add_damage_types(storages: &mut Storages) -> Result<(), SaveError> {
+    for damage in storage_components(storages, "SufferDamage")? {
+        if let Some(amounts) = damage.get_mut("amount").and_then(Value::as_array_mut) {
+            for amount in amounts.iter_mut().filter_map(Value::as_object_mut) {
+                amount.entry("damage_type").or_insert(Value::Null);
+            }
+        }
+    }
+
+    let slashing = serde_json::to_value(DamageType::Slashing)?;
+    for weapon in storage_components(storages, "MeleeWeapon")? {
+        if let Some(weapon) = weapon.as_object_mut() {
+            weapon
+                .entry("damage_type")
+                .or_insert_with(|| slashing.clone());
+        }
+    }
+
+    let blasts = markers_with(storages, "AreaOfEffect")?;
+    let traps = markers_with(storages, "EntryTrigger")?;
+    let corrupt = || SaveError::Corrupt("InflictsDamage isn't a component list".to_string());
+    let entries = match storages.get_mut("InflictsDamage") {
+        None => return Ok(()),
+        Some(entries) => entries.as_array_mut().ok_or_else(corrupt)?,
+    };
+    for entry in entries.iter_mut() {
+        let damage_type = if blasts.contains(&entry["marker"]) {
+            DamageType::Fire
+        } else if traps.contains(&entry["marker"]) {
+            DamageType::Blunt
+        } else {
+            DamageType::Piercing
+        };
+        let damage_type = serde_json::to_value(damage_type)?;
+        if let Some(damage) = entry
+            .get_mut("components")
+            .and_then(|components| components.get_mut(0))
+            .and_then(Value::as_object_mut)
+        {
+            damage.entry("damage_type").or_insert(damage_type);
+        }
+    }
+    Ok(())
+}
+
+/// Schema 10 -> 11: confusion is one status effect among many. Confused creatures carry it
+/// in their effects, and the items that confused them inflict it.
+fn add_status_effects(storages: &mut Storages) -> Result<(), SaveError> {
+    let items = markers_with(storages, "Item")?;
+    let corrupt = || SaveError::Corrupt("Confusion isn't a component list".to_string());
+    let entries = match storages.remove("Confusion") {
+        None => return Ok(()),
+        Some(Value::Array(entries)) => entries,
+        Some(_) => return Err(corrupt()),
+    };
+    let mut inflicts_status = Vec::new();
+    let mut status_effects = Vec::new();
+    for entry in entries.iter() {
+        let marker = entry["marker"].clone();
+        let (mut inflicts, mut effects) = (Value::Null, Value::Null);
+        if let Some(turns) = entry["components"][0]["turns"].as_i64() {
+            let effect = StatusEffect {
+                kind: StatusKind::Confusion,
+                turns: turns as i32,
+                potency: 0,
+            };
+            if items.contains(&marker) {
+                inflicts = serde_json::to_value(InflictsStatus { effect })?;
+            } else {
+                effects = serde_json::to_value(StatusEffects {
+                    effects: vec![effect],
+                })?;
+            }
+        }
+        inflicts_status.push(serde_json::json!({
+            "marker": marker.clone(),
+            "components": [inflicts],
+        }));
+        status_effects.push(serde_json::json!({
+            "marker": marker,
+            "components": [effects],
+        }));
+    }
+    storages
+        .entry("InflictsStatus")
+        .or_insert_with(|| Value::Array(inflicts_status));
+    storages
+        .entry("StatusEffects")
+        .or_insert_with(|| Value::Array(status_effects));
+    Ok(())
+}
+
+/// Schema 11 -> 12: the seed older runs started from was never kept, so they carry on from
+/// seed 0.
+fn add_run_seed(storages: &mut Storages) -> Result<(), SaveError> {
+    for helper in storage_components(storages, "SerializationHelper")? {
+        if let Some(helper) = helper.as_object_mut() {
+            helper.entry("seed").or_insert_with(|| Value::from(0));
+        }
+    }
+    Ok(())
+}
+
+/// Markers of the saved entities that have a component in the `name` storage.
+fn markers_with(storages: &Storages, name: &str) -> Result<Vec<Value>, SaveError> {
+    let corrupt = || SaveError::Corrupt(format!("{} isn't a component list", name));
+    let entries = match storages.get(name) {
+        None => return Ok(Vec::new()),
+        Some(entries) => entries.as_array().ok_or_else(corrupt)?,
+    };
+    Ok(entries
+        .iter()
+        .filter(|entry| !entry["components"][0].is_null())
+        .map(|entry| entry["marker"].clone())
+        .collect())
+}
+
+/// Adds a storage called `name` holding `component` for every entity that has a component
+/// in one of the `owners` storages. Saves that already have the storage keep it.
+fn give_component<C: Serialize>(
+    storages: &mut Storages,
+    owners: &[&str],
+    name: &str,
+    component: C,
+) -> Result<(), SaveError> {
+    let component = serde_json::to_value(component)?;
+    let mut entries = Vec::new();
+    for owner in owners {
+        let corrupt = || SaveError::Corrupt(format!("{} isn't a component list", owner));
+        let saved = match storages.get(*owner) {
+            None => continue,
+            Some(saved) => saved.as_array().ok_or_else(corrupt)?,
+        };
+        for entry in saved
+            .iter()
+            .filter(|entry| !entry["components"][0].is_null())
+        {
+            entries.push(serde_json::json!({
+                "marker": entry["marker"].clone(),
+                "components": [component.clone()],
+            }));
+        }
+    }
+    storages
+        .entry(name)
+        .or_insert_with(|| Value::Array(entries));
+    Ok(())
+}
+
+/// The saved component values in one storage, for migrations to edit in place. Every saved
+/// entity has an entry in every storage, `null` if it doesn't have that component.
+fn storage_components<'a>(
+    storages: &'a mut Storages,
+    name: &str,
+) -> Result<Vec<&'a mut Value>, SaveError> {
+    let corrupt = || SaveError::Corrupt(format!("{} isn't a component list", name));
+    let entries = match storages.get_mut(name) {
+        None => return Ok(Vec::new()),
+        Some(entries) => entries.as_array_mut().ok_or_else(corrupt)?,
+    };
+    entries
+        .iter_mut()
+        .map(|entry| {
+            entry
+                .get_mut("components")
+                .and_then(|components| components.get_mut(0))
+                .ok_or_else(corrupt)
+        })
+        .collect()
+}
+
+/// Replaces the current game with the one in `data`. Nothing is touched unless the save
+/// parses and upgrades cleanly; if it still fails after that, the world is left empty and
+/// the caller should start over.
+pub fn load_from_str(ecs: &mut World, data: &str) -> Result<(), SaveError> {
+    load_from_bytes(ecs, data.as_bytes())
+}
+
+/// Like `load_from_str`, for a save in either format.
+pub fn load_from_bytes(ecs: &mut World, data: &[u8]) -> Result<(), SaveError> {
+    let mut save = parse_save(data)?;
+    migrate(&mut save)?;
+    for required in &["Player", "SerializationHelper"] {
+        let present = save
+            .storages
+            .get(*required)
+            .and_then(Value::as_array)
+            .is_some_and(|entries| {
+                entries
+                    .iter()
+                    .any(|entry| !entry["components"][0].is_null())
+            });
+        if !present {
+            return Err(SaveError::Corrupt(format!("no {} saved", required)));
+        }
+    }
+
+    for helper in storage_components(&mut save.storages, "SerializationHelper")? {
+        if helper.is_null() {
+            continue;
+        }
+        check_map_size(&helper["map"])?;
+        if let Some(stored) = helper["dungeon"]["maps"].as_object() {
+            for map in stored.values() {
+                check_map_size(map)?;
+            }
+        }
+    }
+
     {
         // Delete everything
         let mut to_delete = Vec::new();
@@ -119,9 +976,7 @@ pub fn load_game(ecs: &mut World) {
         }
     }
 
-    let data = fs::read_to_string("./savegame.json").unwrap();
-    let mut de = serde_json::Deserializer::from_str(&data);
-
+    let mut storages = save.storages;
     {
         let mut d = (
             &mut ecs.entities(),
@@ -129,48 +984,11 @@ pub fn load_game(ecs: &mut World) {
             &mut ecs.write_resource::<SimpleMarkerAllocator<SerializeMe>>(),
         );
 
-        deserialize_individually!(
-            ecs,
-            de,
-            d,
-            Position,
-            Renderable,
-            Player,
-            Viewshed,
-            Monster,
-            Name,
-            BlocksTile,
-            CombatStats,
-            SufferDamage,
-            WantsToMelee,
-            Item,
-            Consumable,
-            Ranged,
-            InflictsDamage,
-            AreaOfEffect,
-            Confusion,
-            ProvidesHealing,
-            ProvidesFood,
-            RevealsMap,
-            MeleePowerBonus,
-            DefenseBonus,
-            InBackpack,
-            Equippable,
-            Equipped,
-            WantsToPickupItem,
-            WantsToUseItem,
-            WantsToDropItem,
-            WantsToRemoveItem,
-            HungerClock,
-            Hidden,
-            EntryTrigger,
-            EntityMoved,
-            SingleActivation,
-            SerializationHelper
-        );
+        with_all_components!(deserialize_individually, ecs, storages, d);
     }
 
     let mut deleteme: Option<Entity> = None;
+    let mut seed = None;
     {
         let entities = ecs.entities();
         let helper = ecs.read_storage::<SerializationHelper>();
@@ -179,7 +997,10 @@ pub fn load_game(ecs: &mut World) {
         for (e, h) in (&entities, &helper).join() {
             let mut worldmap = ecs.write_resource::<super::map::Map>();
             *worldmap = h.map.clone();
-            worldmap.tile_content = vec![Vec::new(); super::map::MAP_COUNT];
+            worldmap.tile_content = vec![Vec::new(); (worldmap.width * worldmap.height) as usize];
+            *ecs.write_resource::<MasterDungeonMap>() = h.dungeon.clone();
+            *ecs.write_resource::<GameClock>() = h.clock;
+            seed = Some((h.seed, h.clock.tick));
             deleteme = Some(e);
         }
         for (e, _p, pos) in (&entities, &player, &position).join() {
@@ -189,12 +1010,51 @@ pub fn load_game(ecs: &mut World) {
             *player_resource = e;
         }
     }
-    ecs.delete_entity(deleteme.unwrap())
-        .expect("Unable to delete helper");
+    if let Some(helper) = deleteme {
+        ecs.delete_entity(helper).expect("Unable to delete helper");
+    }
+    // The generator's state isn't saved. It starts over from the run's seed mixed with the
+    // clock, so a save plays out the same every time it's loaded.
+    if let Some((seed, tick)) = seed {
+        ecs.insert(RunSeed { seed });
+        ecs.insert(rltk::RandomNumberGenerator::seeded(
+            seed ^ tick.wrapping_mul(0x9E37_79B9_7F4A_7C15),
+        ));
+    }
+    Ok(())
 }
 
-pub fn delete_save() {
-    if Path::new("./savegame.json").exists() {
-        std::fs::remove_file("./savegame.json").expect("Unable to delete file");
+/// Fails unless every per-tile vector in a saved map has one entry per tile, so a damaged
+/// save can't load a map that panics the first time it's read.
+fn check_map_size(map: &Value) -> Result<(), SaveError> {
+    let corrupt = |what: &str| SaveError::Corrupt(format!("map {} doesn't fit the map", what));
+    let tiles = match (map["width"].as_u64(), map["height"].as_u64()) {
+        (Some(width), Some(height)) => width * height,
+        _ => return Err(corrupt("size")),
+    };
+    if map["tiles"].as_array().map(Vec::len) != Some(tiles as usize) {
+        return Err(corrupt("tiles"));
+    }
+    for field in &["revealed_tiles", "visible_tiles", "blocked"] {
+        let runs = map[*field].as_array().ok_or_else(|| corrupt(field))?;
+        let mut length = 0;
+        for run in runs {
+            length += run.as_u64().ok_or_else(|| corrupt(field))?;
+        }
+        if length != tiles {
+            return Err(corrupt(field));
+        }
     }
+    Ok(())
+}
+
+/// Loads the game in `slot`. A character only ever has one save, so the slot is emptied once
+/// the game is back in play; it is written again when the player next saves.
+pub fn load_game(ecs: &mut World, slot: usize) -> Result<(), SaveError> {
+    let path = ecs.fetch::<SaveSlots>().path(slot);
+    let data = fs::read(&path)?;
+    load_from_bytes(ecs, &data)?;
+    let mut slots = ecs.write_resource::<SaveSlots>();
+    slots.current = Some(slot);
+    slots.delete(slot)
 }
@@ -0,0 +1,262 @@
+use rltk::RGB;
+use specs::prelude::*;
+
+use super::{
+    gamelog::{GameLog, LogCategory, LogEntry},
+    CombatStats, DamageType, MyTurn, Name, Position, RunState, StatusEffect, StatusEffects,
+    StatusKind, SufferDamage, Viewshed,
+};
+
+/// What happens when an effect lands on a creature already suffering one of its kind.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Stacking {
+    /// The longer duration and the stronger potency win.
+    Refresh,
+    /// Durations add up.
+    Extend,
+    /// Potencies add up, and the longer duration wins.
+    Intensify,
+}
+
+impl StatusKind {
+    pub const ALL: [StatusKind; 8] = [
+        StatusKind::Confusion,
+        StatusKind::Poison,
+        StatusKind::Burning,
+        StatusKind::Regeneration,
+        StatusKind::Slow,
+        StatusKind::Haste,
+        StatusKind::Paralysis,
+        StatusKind::Blindness,
+    ];
+
+    /// How the effect reads next to its bearer: "You are no longer confused."
+    pub fn name(self) -> &'static str {
+        match self {
+            StatusKind::Confusion => "confused",
+            StatusKind::Poison => "poisoned",
+            StatusKind::Burning => "burning",
+            StatusKind::Regeneration => "regenerating",
+            StatusKind::Slow => "slowed",
+            StatusKind::Haste => "hasted",
+            StatusKind::Paralysis => "paralyzed",
+            StatusKind::Blindness => "blind",
+        }
+    }
+
+    pub fn color(self) -> RGB {
+        match self {
+            StatusKind::Confusion => RGB::named(rltk::MAGENTA),
+            StatusKind::Poison => RGB::named(rltk::GREEN),
+            StatusKind::Burning => RGB::named(rltk::ORANGE),
+            StatusKind::Regeneration => RGB::named(rltk::PINK),
+            StatusKind::Slow => RGB::named(rltk::LIGHT_BLUE),
+            StatusKind::Haste => RGB::named(rltk::CYAN),
+            StatusKind::Paralysis => RGB::named(rltk::YELLOW),
+            StatusKind::Blindness => RGB::named(rltk::GRAY),
+        }
+    }
+
+    pub fn stacking(self) -> Stacking {
+        match self {
+            StatusKind::Poison => Stacking::Intensify,
+            StatusKind::Regeneration | StatusKind::Blindness => Stacking::Extend,
+            StatusKind::Confusion
+            | StatusKind::Burning
+            | StatusKind::Slow
+            | StatusKind::Haste
+            | StatusKind::Paralysis => Stacking::Refresh,
+        }
+    }
+
+    /// The effect this one replaces when it lands, if any.
+    pub fn opposite(self) -> Option<StatusKind> {
+        match self {
+            StatusKind::Slow => Some(StatusKind::Haste),
+            StatusKind::Haste => Some(StatusKind::Slow),
+            _ => None,
+        }
+    }
+}
+
+impl StatusEffects {
+    pub fn get(&self, kind: StatusKind) -> Option<&StatusEffect> {
+        self.effects.iter().find(|effect| effect.kind == kind)
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.get(kind).is_some()
+    }
+
+    /// Adds `effect`, stacking it onto one of the same kind and replacing its opposite.
+    pub fn add(&mut self, effect: StatusEffect) {
+        if let Some(opposite) = effect.kind.opposite() {
+            self.effects.retain(|existing| existing.kind != opposite);
+        }
+        match self
+            .effects
+            .iter_mut()
+            .find(|existing| existing.kind == effect.kind)
+        {
+            None => self.effects.push(effect),
+            Some(existing) => match effect.kind.stacking() {
+                Stacking::Refresh => {
+                    existing.turns = existing.turns.max(effect.turns);
+                    existing.potency = existing.potency.max(effect.potency);
+                }
+                Stacking::Extend => {
+                    existing.turns += effect.turns;
+                    existing.potency = existing.potency.max(effect.potency);
+                }
+                Stacking::Intensify => {
+                    existing.turns = existing.turns.max(effect.turns);
+                    existing.potency += effect.potency;
+                }
+            },
+        }
+    }
+
+    /// Puts `effect` on `target`, which needn't have any effects yet.
+    pub fn inflict(store: &mut WriteStorage<StatusEffects>, target: Entity, effect: StatusEffect) {
+        if let Some(effects) = store.get_mut(target) {
+            effects.add(effect);
+        } else {
+            let mut effects = StatusEffects::default();
+            effects.add(effect);
+            store
+                .insert(target, effects)
+                .expect("Unable to insert status effects");
+        }
+    }
+}
+
+/// Whether `entity` is paralyzed, and loses its turns until it wears off.
+pub fn is_paralyzed(ecs: &World, entity: Entity) -> bool {
+    ecs.read_storage::<StatusEffects>()
+        .get(entity)
+        .is_some_and(|effects| effects.has(StatusKind::Paralysis))
+}
+
+/// Energy gained each scheduler tick at `speed`: double when hasted, half when slowed.
+pub fn effective_speed(speed: i32, effects: Option<&StatusEffects>) -> i32 {
+    match effects {
+        Some(effects) if effects.has(StatusKind::Haste) => speed * 2,
+        Some(effects) if effects.has(StatusKind::Slow) => (speed + 1) / 2,
+        _ => speed,
+    }
+}
+
+/// How far a creature can see with everything affecting it.
+pub fn sight_range(range: i32, effects: Option<&StatusEffects>) -> i32 {
+    match effects {
+        Some(effects) if effects.has(StatusKind::Blindness) => range.min(1),
+        _ => range,
+    }
+}
+
+/// Runs every effect on each of its bearer's turns, the player's included, ahead of any
+/// item used that turn: poison and burning hurt, and regeneration heals. Each then has a
+/// turn less to run, and says so when it wears off.
+pub struct StatusEffectSystem {}
+
+impl<'a> System<'a> for StatusEffectSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, RunState>,
+        ReadStorage<'a, MyTurn>,
+        ReadStorage<'a, Position>,
+        WriteStorage<'a, StatusEffects>,
+        WriteStorage<'a, CombatStats>,
+        WriteStorage<'a, SufferDamage>,
+        WriteStorage<'a, Viewshed>,
+        ReadStorage<'a, Name>,
+        WriteExpect<'a, GameLog>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            runstate,
+            my_turn,
+            positions,
+            mut status_effects,
+            mut combat_stats,
+            mut suffer_damage,
+            mut viewsheds,
+            names,
+            mut log,
+        ) = data;
+
+        let mut recovered = Vec::new();
+        // Creatures left on other levels have no position, and their effects wait for them
+        for (entity, statuses, _pos) in (&entities, &mut status_effects, &positions).join() {
+            let proceed = match *runstate {
+                RunState::PlayerTurn => entity == *player_entity,
+                RunState::MonsterTurn => entity != *player_entity && my_turn.contains(entity),
+                _ => false,
+            };
+            if !proceed {
+                continue;
+            }
+
+            for effect in statuses.effects.iter_mut() {
+                match effect.kind {
+                    StatusKind::Poison => SufferDamage::new_damage(
+                        &mut suffer_damage,
+                        entity,
+                        effect.potency,
+                        None,
+                        Some(DamageType::Poison),
+                    ),
+                    StatusKind::Burning => SufferDamage::new_damage(
+                        &mut suffer_damage,
+                        entity,
+                        effect.potency,
+                        None,
+                        Some(DamageType::Fire),
+                    ),
+                    StatusKind::Regeneration => {
+                        if let Some(stats) = combat_stats.get_mut(entity) {
+                            stats.hp = (stats.hp + effect.potency).min(stats.max_hp);
+                        }
+                    }
+                    _ => {}
+                }
+                effect.turns -= 1;
+            }
+
+            for effect in statuses.effects.iter().filter(|effect| effect.turns < 1) {
+                if effect.kind == StatusKind::Blindness {
+                    if let Some(viewshed) = viewsheds.get_mut(entity) {
+                        viewshed.dirty = true;
+                    }
+                }
+                let entry = if entity == *player_entity {
+                    LogEntry::new(LogCategory::Status).text("You are no longer ")
+                } else if let Some(name) = names.get(entity) {
+                    LogEntry::new(LogCategory::Status)
+                        .name(&name.name)
+                        .text(" is no longer ")
+                } else {
+                    continue;
+                };
+                log.push(
+                    entry
+                        .colored(effect.kind.name(), effect.kind.color())
+                        .text("."),
+                );
+            }
+            statuses.effects.retain(|effect| effect.turns > 0);
+            if statuses.effects.is_empty() {
+                recovered.push(entity);
+            }
+        }
+
+        for entity in recovered {
+            status_effects.remove(entity);
+        }
+    }
+}
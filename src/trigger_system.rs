@@ -1,8 +1,10 @@
 use specs::*;
 
 use super::{
-    gamelog::GameLog, particle_system::ParticleBuilder, EntityMoved, EntryTrigger, Hidden,
-    InflictsDamage, Map, Name, Position, SingleActivation, SufferDamage,
+    gamelog::{GameLog, LogCategory, LogEntry},
+    particle_system::ParticleBuilder,
+    EntityMoved, EntryTrigger, Hidden, InflictsDamage, Map, Name, Position, SingleActivation,
+    SufferDamage,
 };
 
 pub struct TriggerSystem {}
@@ -50,7 +52,11 @@ impl<'a> System<'a> for TriggerSystem {
                         Some(_trigger) => {
                             let name = names.get(*entity_id);
                             if let Some(name) = name {
-                                log.entries.push(format!("{} triggers!", &name.name));
+                                log.push(
+                                    LogEntry::new(LogCategory::Trap)
+                                        .name(&name.name)
+                                        .text(" triggers!"),
+                                );
                             }
 
                             hidden.remove(*entity_id);
@@ -69,6 +75,8 @@ impl<'a> System<'a> for TriggerSystem {
                                     &mut inflict_damage,
                                     entity,
                                     _damage.damage,
+                                    None,
+                                    Some(_damage.damage_type),
                                 );
                             }
 
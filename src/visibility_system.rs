@@ -1,4 +1,7 @@
-use super::{gamelog::GameLog, Hidden, Map, Name, Player, Position, Viewshed};
+use super::{
+    gamelog::{GameLog, LogCategory, LogEntry},
+    status_effects, Hidden, Map, Name, Player, Position, StatusEffects, Viewshed,
+};
 use rltk::{field_of_view, Point};
 use specs::prelude::*;
 
@@ -7,7 +10,6 @@ pub struct VisibilitySystem {}
 impl<'a> System<'a> for VisibilitySystem {
     type SystemData = (
         WriteExpect<'a, Map>,
-        Entities<'a>,
         WriteStorage<'a, Viewshed>,
         WriteStorage<'a, Position>,
         ReadStorage<'a, Player>,
@@ -15,40 +17,49 @@ impl<'a> System<'a> for VisibilitySystem {
         WriteExpect<'a, rltk::RandomNumberGenerator>,
         WriteExpect<'a, GameLog>,
         ReadStorage<'a, Name>,
+        ReadStorage<'a, StatusEffects>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut map, entities, mut viewshed, pos, player, mut hidden, mut rng, mut log, names) =
+        let (mut map, mut viewshed, pos, player, hidden, mut rng, mut log, names, status_effects) =
             data;
 
-        for (entity, viewshed, pos) in (&entities, &mut viewshed, &pos).join() {
-            if viewshed.dirty {
-                viewshed.dirty = false;
-                viewshed.visible_tiles =
-                    field_of_view(Point::new(pos.x, pos.y), viewshed.range, &*map);
-                viewshed
-                    .visible_tiles
-                    .retain(|p| p.x >= 0 && p.x < map.width && p.y >= 0 && p.y < map.height);
+        // Everyone but the player only looks, so their fields of view are worked out in parallel
+        {
+            let map = &*map;
+            (&mut viewshed, &pos, !&player, status_effects.maybe())
+                .par_join()
+                .for_each(|(viewshed, pos, _, effects)| {
+                    update_viewshed(viewshed, pos, map, effects);
+                });
+        }
 
-                let _p: Option<&Player> = player.get(entity);
-                if let Some(_p) = _p {
-                    for t in map.visible_tiles.iter_mut() {
-                        *t = false
-                    }
-                    for vis in viewshed.visible_tiles.iter() {
-                        let idx = map.xy_idx(vis.x, vis.y);
-                        map.revealed_tiles[idx] = true;
-                        map.visible_tiles[idx] = true;
+        for (viewshed, pos, _player, effects) in
+            (&mut viewshed, &pos, &player, status_effects.maybe()).join()
+        {
+            if !update_viewshed(viewshed, pos, &map, effects) {
+                continue;
+            }
+            for t in map.visible_tiles.iter_mut() {
+                *t = false
+            }
+            for vis in viewshed.visible_tiles.iter() {
+                let idx = map.xy_idx(vis.x, vis.y);
+                map.revealed_tiles[idx] = true;
+                map.visible_tiles[idx] = true;
 
-                        for e in map.tile_content[idx].iter() {
-                            let maybe_hidden = hidden.get(*e);
-                            if let Some(_maybe_hidden) = maybe_hidden {
-                                if rng.roll_dice(1, 24) == 1 {
-                                    let name = names.get(*e);
-                                    if let Some(_name) = name {
-                                        log.entries.push(format!("You spotted a {}.", &_name.name));
-                                    }
-                                }
+                for e in map.tile_content[idx].iter() {
+                    let maybe_hidden = hidden.get(*e);
+                    if let Some(_maybe_hidden) = maybe_hidden {
+                        if rng.roll_dice(1, 24) == 1 {
+                            let name = names.get(*e);
+                            if let Some(_name) = name {
+                                log.push(
+                                    LogEntry::new(LogCategory::Trap)
+                                        .text("You spotted a ")
+                                        .name(&_name.name)
+                                        .text("."),
+                                );
                             }
                         }
                     }
@@ -57,3 +68,23 @@ impl<'a> System<'a> for VisibilitySystem {
         }
     }
 }
+
+/// Recomputes a dirty viewshed, returning whether it changed.
+fn update_viewshed(
+    viewshed: &mut Viewshed,
+    pos: &Position,
+    map: &Map,
+    effects: Option<&StatusEffects>,
+) -> bool {
+    // Blind eyes are rechecked every time, so sight is cut short as soon as it's lost
+    let range = status_effects::sight_range(viewshed.range, effects);
+    if !viewshed.dirty && range == viewshed.range {
+        return false;
+    }
+    viewshed.dirty = false;
+    viewshed.visible_tiles = field_of_view(Point::new(pos.x, pos.y), range, map);
+    viewshed
+        .visible_tiles
+        .retain(|p| p.x >= 0 && p.x < map.width && p.y >= 0 && p.y < map.height);
+    true
+}
@@ -0,0 +1,81 @@
+use hello_rltk::camera::{Camera, VIEW_HEIGHT, VIEW_WIDTH};
+use hello_rltk::map_builders::level_size;
+use hello_rltk::Map;
+use rltk::Point;
+
+#[test]
+fn screen_sized_map_never_scrolls() {
+    let map = Map::new(1, VIEW_WIDTH, VIEW_HEIGHT);
+    for center in &[Point::new(0, 0), Point::new(40, 20), Point::new(79, 42)] {
+        let camera = Camera::centered_on(*center, &map);
+        assert_eq!((camera.min_x, camera.min_y), (0, 0));
+        assert_eq!(camera.world_to_screen(*center), Some(*center));
+    }
+}
+
+#[test]
+fn camera_follows_the_player_on_large_maps() {
+    let map = Map::new(1, 160, 86);
+    let player = Point::new(80, 43);
+    let camera = Camera::centered_on(player, &map);
+
+    assert_eq!(
+        camera.world_to_screen(player),
+        Some(Point::new(VIEW_WIDTH / 2, VIEW_HEIGHT / 2))
+    );
+    assert_eq!(camera.world_to_screen(Point::new(0, 0)), None);
+}
+
+#[test]
+fn camera_stops_at_the_map_edges() {
+    let map = Map::new(1, 160, 86);
+
+    let top_left = Camera::centered_on(Point::new(3, 2), &map);
+    assert_eq!((top_left.min_x, top_left.min_y), (0, 0));
+
+    let bottom_right = Camera::centered_on(Point::new(159, 85), &map);
+    assert_eq!(
+        (bottom_right.min_x, bottom_right.min_y),
+        (160 - VIEW_WIDTH, 86 - VIEW_HEIGHT)
+    );
+    assert_eq!(
+        bottom_right.world_to_screen(Point::new(159, 85)),
+        Some(Point::new(VIEW_WIDTH - 1, VIEW_HEIGHT - 1))
+    );
+}
+
+#[test]
+fn screen_and_world_round_trip() {
+    let map = Map::new(1, 123, 77);
+    let camera = Camera::centered_on(Point::new(70, 50), &map);
+    for y in 0..VIEW_HEIGHT {
+        for x in 0..VIEW_WIDTH {
+            let screen = Point::new(x, y);
+            let world = camera.screen_to_world(screen, &map).unwrap();
+            assert_eq!(camera.world_to_screen(world), Some(screen));
+        }
+    }
+}
+
+#[test]
+fn ui_panel_and_space_past_small_maps_are_not_on_the_map() {
+    let map = Map::new(1, 40, 20);
+    let camera = Camera::centered_on(Point::new(10, 10), &map);
+    assert_eq!(
+        camera.screen_to_world(Point::new(5, VIEW_HEIGHT + 1), &map),
+        None
+    );
+    assert_eq!(camera.screen_to_world(Point::new(50, 5), &map), None);
+    assert_eq!(
+        camera.screen_to_world(Point::new(39, 19), &map),
+        Some(Point::new(39, 19))
+    );
+}
+
+#[test]
+fn deeper_levels_outgrow_the_screen() {
+    assert_eq!(level_size(1), (VIEW_WIDTH, VIEW_HEIGHT));
+    let (width, height) = level_size(4);
+    assert!(width > VIEW_WIDTH && height > VIEW_HEIGHT);
+    assert_eq!(level_size(100), (VIEW_WIDTH * 2, VIEW_HEIGHT * 2));
+}
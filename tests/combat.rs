@@ -0,0 +1,64 @@
+use hello_rltk::combat::{
+    armor_class, dice_string, mitigate, resolve_attack, roll_damage, AttackRoll, DamageResponse,
+    BASE_ARMOR_CLASS,
+};
+use rltk::{DiceType, RandomNumberGenerator};
+
+#[test]
+fn natural_rolls_decide_before_bonuses() {
+    assert_eq!(resolve_attack(20, -100, 100), AttackRoll::Critical);
+    assert_eq!(resolve_attack(1, 100, 0), AttackRoll::Fumble);
+    assert!(resolve_attack(20, 0, 0).hits());
+    assert!(!resolve_attack(1, 0, 0).hits());
+}
+
+#[test]
+fn other_rolls_hit_when_they_reach_the_armor_class() {
+    assert_eq!(resolve_attack(10, 2, 12), AttackRoll::Hit);
+    assert_eq!(resolve_attack(10, 1, 12), AttackRoll::Miss);
+    assert_eq!(resolve_attack(19, 0, 30), AttackRoll::Miss);
+    assert_eq!(armor_class(0, 0), BASE_ARMOR_CLASS);
+    assert_eq!(armor_class(2, 1), BASE_ARMOR_CLASS + 3);
+}
+
+#[test]
+fn critical_hits_roll_the_dice_twice() {
+    let mut rng = RandomNumberGenerator::seeded(3);
+    let dice = DiceType::new(1, 6, 2);
+    let normal: Vec<i32> = (0..1000)
+        .map(|_| roll_damage(&mut rng, dice, false))
+        .collect();
+    let critical: Vec<i32> = (0..1000)
+        .map(|_| roll_damage(&mut rng, dice, true))
+        .collect();
+
+    assert_eq!(normal.iter().min(), Some(&3));
+    assert_eq!(normal.iter().max(), Some(&8));
+    assert_eq!(critical.iter().min(), Some(&4));
+    assert_eq!(critical.iter().max(), Some(&14));
+}
+
+#[test]
+fn dice_read_in_the_usual_notation() {
+    assert_eq!(dice_string(DiceType::new(1, 6, 0)), "1d6");
+    assert_eq!(dice_string(DiceType::new(2, 4, 1)), "2d4+1");
+    assert_eq!(dice_string(DiceType::new(1, 8, -1)), "1d8-1");
+}
+
+#[test]
+fn immunity_beats_resistance_and_vulnerability() {
+    assert_eq!(
+        mitigate(7, false, false, false),
+        (7, DamageResponse::Normal)
+    );
+    assert_eq!(
+        mitigate(7, true, false, false),
+        (3, DamageResponse::Resisted)
+    );
+    assert_eq!(
+        mitigate(7, false, false, true),
+        (14, DamageResponse::Vulnerable)
+    );
+    assert_eq!(mitigate(7, true, false, true), (7, DamageResponse::Normal));
+    assert_eq!(mitigate(7, true, true, true), (0, DamageResponse::Immune));
+}
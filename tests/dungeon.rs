@@ -0,0 +1,170 @@
+use hello_rltk::headless::{Command, HeadlessRunner};
+use hello_rltk::*;
+use rltk::Point;
+use specs::prelude::*;
+
+type Snapshot = Vec<(String, i32, i32)>;
+
+/// Everything with a position on the current level except the player, in a stable order.
+fn level_contents(runner: &HeadlessRunner) -> Snapshot {
+    let ecs = &runner.state.ecs;
+    let mut contents: Snapshot = (
+        &ecs.read_storage::<Name>(),
+        &ecs.read_storage::<Position>(),
+        !&ecs.read_storage::<Player>(),
+    )
+        .join()
+        .map(|(name, pos, _)| (name.name.clone(), pos.x, pos.y))
+        .collect();
+    contents.sort();
+    contents
+}
+
+fn depth(runner: &HeadlessRunner) -> i32 {
+    runner.state.ecs.fetch::<Map>().depth
+}
+
+fn player_position(runner: &HeadlessRunner) -> Point {
+    *runner.state.ecs.fetch::<Point>()
+}
+
+fn find_tile(runner: &HeadlessRunner, tile: TileType) -> Point {
+    let map = runner.state.ecs.fetch::<Map>();
+    let idx = map.tiles.iter().position(|t| *t == tile).unwrap();
+    Point::new(idx as i32 % map.width, idx as i32 / map.width)
+}
+
+fn teleport_player(runner: &mut HeadlessRunner, to: Point) {
+    let player = runner.player();
+    let ecs = &mut runner.state.ecs;
+    let mut positions = ecs.write_storage::<Position>();
+    let pos = positions.get_mut(player).unwrap();
+    pos.x = to.x;
+    pos.y = to.y;
+    *ecs.write_resource::<Point>() = to;
+}
+
+fn take_stairs(runner: &mut HeadlessRunner, stairs: TileType, command: Command) {
+    let to = find_tile(runner, stairs);
+    teleport_player(runner, to);
+    runner.submit(command);
+}
+
+#[test]
+fn levels_are_kept_when_the_player_leaves() {
+    let mut runner = HeadlessRunner::new(7);
+    let stash = runner
+        .state
+        .ecs
+        .create_entity()
+        .with(Position { x: 1, y: 1 })
+        .with(Item {})
+        .with(Name {
+            name: "Stashed Loot".to_string(),
+        })
+        .build();
+    let first_tiles = runner.state.ecs.fetch::<Map>().tiles.clone();
+    let down_stairs = find_tile(&runner, TileType::DownStairs);
+    teleport_player(&mut runner, down_stairs);
+    let first_level = level_contents(&runner);
+
+    runner.submit(Command::Descend);
+    assert_eq!(depth(&runner), 2);
+    assert!(level_contents(&runner)
+        .iter()
+        .all(|(name, _, _)| name != "Stashed Loot"));
+    {
+        let map = runner.state.ecs.fetch::<Map>();
+        let here = player_position(&runner);
+        assert_eq!(map.tiles[map.xy_idx(here.x, here.y)], TileType::UpStairs);
+    }
+    let second_level = level_contents(&runner);
+
+    take_stairs(&mut runner, TileType::UpStairs, Command::Ascend);
+    assert_eq!(depth(&runner), 1);
+    assert_eq!(player_position(&runner), down_stairs);
+    assert!(runner.state.ecs.fetch::<Map>().tiles == first_tiles);
+    assert_eq!(level_contents(&runner), first_level);
+    assert!(runner.state.ecs.is_alive(stash));
+
+    runner.submit(Command::Descend);
+    assert_eq!(depth(&runner), 2);
+    assert_eq!(level_contents(&runner), second_level);
+}
+
+#[test]
+fn creatures_left_behind_give_up_their_turns() {
+    let mut runner = HeadlessRunner::new(7);
+    let goblin = raws::spawn_named(&mut runner.state.ecs, "Goblin", 1, 1).unwrap();
+    runner
+        .state
+        .ecs
+        .write_storage::<MyTurn>()
+        .insert(goblin, MyTurn {})
+        .unwrap();
+
+    dungeon::freeze_level_entities(&mut runner.state.ecs);
+    let ecs = &runner.state.ecs;
+    assert!(ecs.read_storage::<OtherLevelPosition>().contains(goblin));
+    assert!(!ecs.read_storage::<MyTurn>().contains(goblin));
+}
+
+#[test]
+fn cannot_climb_above_the_first_level() {
+    let mut runner = HeadlessRunner::new(3);
+    assert!(!runner
+        .state
+        .ecs
+        .fetch::<Map>()
+        .tiles
+        .contains(&TileType::UpStairs));
+    runner.submit(Command::Ascend);
+    assert_eq!(depth(&runner), 1);
+}
+
+#[test]
+fn new_levels_are_only_generated_once() {
+    let mut runner = HeadlessRunner::new(11);
+    take_stairs(&mut runner, TileType::DownStairs, Command::Descend);
+    take_stairs(&mut runner, TileType::DownStairs, Command::Descend);
+    assert_eq!(depth(&runner), 3);
+    let third_tiles = runner.state.ecs.fetch::<Map>().tiles.clone();
+
+    take_stairs(&mut runner, TileType::UpStairs, Command::Ascend);
+    take_stairs(&mut runner, TileType::UpStairs, Command::Ascend);
+    assert_eq!(depth(&runner), 1);
+    take_stairs(&mut runner, TileType::DownStairs, Command::Descend);
+    take_stairs(&mut runner, TileType::DownStairs, Command::Descend);
+
+    assert_eq!(depth(&runner), 3);
+    assert!(runner.state.ecs.fetch::<Map>().tiles == third_tiles);
+}
+
+#[test]
+fn every_level_below_the_first_has_stairs_both_ways() {
+    for seed in 1..=20 {
+        let mut runner = HeadlessRunner::new(seed);
+        let player = runner.player();
+        runner
+            .state
+            .ecs
+            .write_storage::<CombatStats>()
+            .get_mut(player)
+            .unwrap()
+            .hp = i32::MAX / 2;
+        for expected in 2..=5 {
+            take_stairs(&mut runner, TileType::DownStairs, Command::Descend);
+            assert_eq!(depth(&runner), expected);
+            let map = runner.state.ecs.fetch::<Map>();
+            for stairs in &[TileType::UpStairs, TileType::DownStairs] {
+                assert!(
+                    map.tiles.contains(stairs),
+                    "no {:?}, seed {} depth {}",
+                    stairs,
+                    seed,
+                    expected
+                );
+            }
+        }
+    }
+}
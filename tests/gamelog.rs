@@ -0,0 +1,56 @@
+use hello_rltk::gamelog::{GameLog, LogCategory, LogEntry, MAX_LOG_ENTRIES};
+use rltk::RGB;
+
+fn hit(damage: i32) -> LogEntry {
+    LogEntry::new(LogCategory::Combat)
+        .name("Orc")
+        .text(" hurts ")
+        .name("Player")
+        .text(", for ")
+        .damage(damage)
+        .text(" hp")
+}
+
+#[test]
+fn entries_keep_their_category_and_colours() {
+    let entry = hit(3);
+    assert!(entry.category == LogCategory::Combat);
+    assert_eq!(entry.plain_text(), "Orc hurts Player, for 3 hp");
+    assert_eq!(entry.spans[0].color, RGB::named(rltk::YELLOW));
+    assert_eq!(entry.spans[4].text, "3");
+    assert_eq!(entry.spans[4].color, RGB::named(rltk::RED));
+    assert_eq!(entry.spans[1].color, LogCategory::Combat.color());
+}
+
+#[test]
+fn repeated_lines_are_counted_instead_of_repeated() {
+    let mut log = GameLog::new();
+    log.push(hit(3));
+    log.push(hit(3));
+    log.push(hit(3));
+    assert_eq!(log.entries().len(), 1);
+    assert_eq!(
+        log.entries().next().unwrap().plain_text(),
+        "Orc hurts Player, for 3 hp x3"
+    );
+
+    log.push(hit(4));
+    log.push(hit(3));
+    log.say(LogCategory::Hunger, "Orc hurts Player, for 3 hp");
+    let counts: Vec<u32> = log.entries().map(|entry| entry.count).collect();
+    assert_eq!(counts, vec![3, 1, 1, 1]);
+}
+
+#[test]
+fn the_log_is_capped() {
+    let mut log = GameLog::new();
+    for i in 0..MAX_LOG_ENTRIES as i32 + 10 {
+        log.push(hit(i));
+    }
+    assert_eq!(log.entries().len(), MAX_LOG_ENTRIES);
+    assert_eq!(log.entries().next().unwrap().spans[4].text, "10");
+    assert_eq!(
+        log.entries().last().unwrap().spans[4].text,
+        (MAX_LOG_ENTRIES + 9).to_string()
+    );
+}
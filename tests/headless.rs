@@ -0,0 +1,699 @@
+use hello_rltk::attributes::{self, Skill};
+use hello_rltk::experience::{xp_to_next_level, LevelUpChoice};
+use hello_rltk::game_clock::GameClock;
+use hello_rltk::gamelog::{GameLog, LogCategory};
+use hello_rltk::headless::{Command, HeadlessRunner};
+use hello_rltk::initiative_system::NORMAL_SPEED;
+use hello_rltk::*;
+use rltk::{Point, RGB};
+use specs::prelude::*;
+
+/// Swaps the generated level for an empty walled room with the player at (10, 10).
+fn arena() -> HeadlessRunner {
+    let mut runner = HeadlessRunner::new(1);
+    let player = runner.player();
+    let ecs = &mut runner.state.ecs;
+
+    let others: Vec<Entity> = (&ecs.entities(), !&ecs.read_storage::<Player>())
+        .join()
+        .map(|(e, _)| e)
+        .collect();
+    ecs.delete_entities(&others).expect("Unable to clear level");
+    ecs.maintain();
+
+    let mut map = Map::new(1, 80, 43);
+    for y in 1..map.height - 1 {
+        for x in 1..map.width - 1 {
+            let idx = map.xy_idx(x, y);
+            map.tiles[idx] = TileType::Floor;
+        }
+    }
+    map.populate_blocked();
+    *ecs.write_resource::<Map>() = map;
+    *ecs.write_resource::<Point>() = Point::new(10, 10);
+    let mut positions = ecs.write_storage::<Position>();
+    let pos = positions.get_mut(player).unwrap();
+    pos.x = 10;
+    pos.y = 10;
+    drop(positions);
+
+    runner.submit(Command::Wait);
+    runner
+}
+
+fn spawn_goblin(ecs: &mut World, x: i32, y: i32, hp: i32, power: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('g'),
+            fg: RGB::named(rltk::RED),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 0,
+        })
+        .with(Viewshed {
+            visible_tiles: Vec::new(),
+            range: 8,
+            dirty: true,
+        })
+        .with(Monster {})
+        .with(Name {
+            name: "Goblin".to_string(),
+        })
+        .with(BlocksTile {})
+        .with(CombatStats { max_hp: hp, hp })
+        .with(Skills {
+            melee: power,
+            ..Skills::default()
+        })
+        .with(Energy {
+            speed: NORMAL_SPEED,
+            energy: 0,
+        })
+        .build()
+}
+
+fn set_speed(runner: &mut HeadlessRunner, entity: Entity, speed: i32) {
+    let mut energy = runner.state.ecs.write_storage::<Energy>();
+    energy.get_mut(entity).unwrap().speed = speed;
+}
+
+fn afflict(
+    runner: &mut HeadlessRunner,
+    entity: Entity,
+    kind: StatusKind,
+    turns: i32,
+    potency: i32,
+) {
+    let mut status_effects = runner.state.ecs.write_storage::<StatusEffects>();
+    let effect = StatusEffect {
+        kind,
+        turns,
+        potency,
+    };
+    match status_effects.get_mut(entity) {
+        Some(effects) => effects.add(effect),
+        None => {
+            status_effects
+                .insert(
+                    entity,
+                    StatusEffects {
+                        effects: vec![effect],
+                    },
+                )
+                .expect("Unable to afflict");
+        }
+    }
+}
+
+fn status(runner: &HeadlessRunner, entity: Entity, kind: StatusKind) -> Option<StatusEffect> {
+    runner
+        .state
+        .ecs
+        .read_storage::<StatusEffects>()
+        .get(entity)
+        .and_then(|effects| effects.get(kind).copied())
+}
+
+/// How many times a confused goblin moving at `speed`, and suffering `effects` as well,
+/// acts while the player, moving at `player_speed`, waits four times. Confusion counts
+/// down once per action.
+fn goblin_actions(speed: i32, player_speed: i32, effects: &[StatusKind]) -> i32 {
+    let mut runner = arena();
+    let player = runner.player();
+    set_speed(&mut runner, player, player_speed);
+    let goblin = spawn_goblin(&mut runner.state.ecs, 30, 30, 10, 1);
+    set_speed(&mut runner, goblin, speed);
+    afflict(&mut runner, goblin, StatusKind::Confusion, 50, 0);
+    for kind in effects {
+        afflict(&mut runner, goblin, *kind, 50, 0);
+    }
+    runner.submit(Command::Wait);
+
+    let confusion =
+        |runner: &HeadlessRunner| status(runner, goblin, StatusKind::Confusion).unwrap().turns;
+    let before = confusion(&runner);
+    runner.run_script(&[Command::Wait; 4]);
+    before - confusion(&runner)
+}
+
+fn hp(runner: &HeadlessRunner, entity: Entity) -> i32 {
+    runner
+        .state
+        .ecs
+        .read_storage::<CombatStats>()
+        .get(entity)
+        .unwrap()
+        .hp
+}
+
+fn log_contains(runner: &HeadlessRunner, text: &str) -> bool {
+    runner
+        .state
+        .ecs
+        .fetch::<GameLog>()
+        .entries()
+        .any(|e| e.plain_text().contains(text))
+}
+
+#[test]
+fn starts_awaiting_input() {
+    let runner = HeadlessRunner::new(1);
+    assert!(runner.runstate() == RunState::AwaitingInput);
+}
+
+const ATTACK_EAST: Command = Command::Move {
+    delta_x: 1,
+    delta_y: 0,
+};
+
+/// Attacks east until `target` dies, giving up after `tries` attacks.
+fn attack_until_dead(runner: &mut HeadlessRunner, target: Entity, tries: usize) -> RunState {
+    let mut end = runner.runstate();
+    for _ in 0..tries {
+        if !runner.state.ecs.is_alive(target) {
+            break;
+        }
+        end = runner.submit(ATTACK_EAST);
+    }
+    assert!(!runner.state.ecs.is_alive(target));
+    end
+}
+
+/// How many attacks `attacker` has made so far, whether they landed or not.
+fn attacks_by(runner: &HeadlessRunner, attacker: &str) -> u32 {
+    runner
+        .state
+        .ecs
+        .fetch::<GameLog>()
+        .entries()
+        .filter(|e| e.category == LogCategory::Combat && !e.plain_text().contains(" is dead"))
+        .filter(|e| e.spans.first().is_some_and(|span| span.text == attacker))
+        .map(|e| e.count)
+        .sum()
+}
+
+#[test]
+fn player_kills_adjacent_goblin() {
+    let mut runner = arena();
+    let goblin = spawn_goblin(&mut runner.state.ecs, 11, 10, 12, 4);
+    runner.submit(Command::Wait);
+
+    attack_until_dead(&mut runner, goblin, 20);
+
+    assert!(
+        log_contains(&runner, "Player hurts Goblin")
+            || log_contains(&runner, "Player critically hits Goblin")
+    );
+    assert!(log_contains(&runner, "Goblin is dead"));
+}
+
+#[test]
+fn only_natural_rolls_beat_overwhelming_odds() {
+    let mut runner = arena();
+    let player = runner.player();
+    let goblin = spawn_goblin(&mut runner.state.ecs, 11, 10, 1000, 0);
+    {
+        let mut skills = runner.state.ecs.write_storage::<Skills>();
+        let skills = skills.get_mut(player).unwrap();
+        skills.melee = 100;
+        skills.defense = 100;
+        runner
+            .state
+            .ecs
+            .write_storage::<CombatStats>()
+            .get_mut(player)
+            .unwrap()
+            .hp = 1000;
+    }
+    runner.submit(Command::Wait);
+    runner.run_script(&[ATTACK_EAST; 60]);
+
+    // The player only fails on a natural 1, the goblin only lands a natural 20
+    assert_eq!(attacks_by(&runner, "Player"), 60);
+    assert_eq!(attacks_by(&runner, "Goblin"), 60);
+    assert!(!log_contains(&runner, "Player misses"));
+    assert!(!log_contains(&runner, "Goblin hurts"));
+    assert!(log_contains(&runner, "Player hurts Goblin"));
+    assert!(log_contains(&runner, "Goblin misses Player"));
+    assert!(runner.state.ecs.is_alive(goblin));
+}
+
+#[test]
+fn faster_things_act_more_often() {
+    assert_eq!(goblin_actions(NORMAL_SPEED, NORMAL_SPEED, &[]), 4);
+    assert_eq!(goblin_actions(NORMAL_SPEED * 2, NORMAL_SPEED, &[]), 8);
+    assert_eq!(goblin_actions(NORMAL_SPEED / 2, NORMAL_SPEED, &[]), 2);
+    assert_eq!(goblin_actions(NORMAL_SPEED, NORMAL_SPEED * 2, &[]), 2);
+}
+
+#[test]
+fn haste_and_slow_change_how_often_things_act() {
+    assert_eq!(
+        goblin_actions(NORMAL_SPEED, NORMAL_SPEED, &[StatusKind::Haste]),
+        8
+    );
+    assert_eq!(
+        goblin_actions(NORMAL_SPEED, NORMAL_SPEED, &[StatusKind::Slow]),
+        2
+    );
+    // Haste replaces slow instead of stacking with it
+    let both = [StatusKind::Slow, StatusKind::Haste];
+    assert_eq!(goblin_actions(NORMAL_SPEED, NORMAL_SPEED, &both), 8);
+}
+
+#[test]
+fn poison_hurts_the_player_each_turn_until_it_wears_off() {
+    let mut runner = arena();
+    let player = runner.player();
+    runner.submit(Command::Wait);
+    // Too hungry to rest the damage off
+    runner
+        .state
+        .ecs
+        .write_storage::<HungerClock>()
+        .get_mut(player)
+        .unwrap()
+        .state = HungerState::Hungry;
+    afflict(&mut runner, player, StatusKind::Poison, 2, 1);
+    afflict(&mut runner, player, StatusKind::Poison, 3, 2);
+    assert_eq!(
+        status(&runner, player, StatusKind::Poison),
+        Some(StatusEffect {
+            kind: StatusKind::Poison,
+            turns: 3,
+            potency: 3,
+        })
+    );
+
+    let before = hp(&runner, player);
+    runner.run_script(&[Command::Wait; 4]);
+    assert_eq!(hp(&runner, player), before - 9);
+    assert!(status(&runner, player, StatusKind::Poison).is_none());
+    assert!(log_contains(&runner, "You are no longer poisoned."));
+}
+
+#[test]
+fn reading_a_scroll_still_ticks_the_players_effects() {
+    let mut runner = arena();
+    let player = runner.player();
+    afflict(&mut runner, player, StatusKind::Poison, 3, 2);
+    let scroll = raws::spawn_named(&mut runner.state.ecs, "Reveal Map Scroll", 10, 10).unwrap();
+    runner.state.ecs.write_storage::<Position>().remove(scroll);
+    runner
+        .state
+        .ecs
+        .write_storage::<InBackpack>()
+        .insert(scroll, InBackpack { owner: player })
+        .unwrap();
+
+    let before = hp(&runner, player);
+    runner.submit(Command::UseItem {
+        item: scroll,
+        target: None,
+    });
+    assert_eq!(hp(&runner, player), before - 2);
+    assert_eq!(
+        status(&runner, player, StatusKind::Poison).unwrap().turns,
+        2
+    );
+}
+
+#[test]
+fn regeneration_heals_and_lasts_longer_when_stacked() {
+    let mut runner = arena();
+    let player = runner.player();
+    runner.submit(Command::Wait);
+    runner
+        .state
+        .ecs
+        .write_storage::<CombatStats>()
+        .get_mut(player)
+        .unwrap()
+        .hp = 5;
+    afflict(&mut runner, player, StatusKind::Regeneration, 2, 2);
+    afflict(&mut runner, player, StatusKind::Regeneration, 2, 1);
+    assert_eq!(
+        status(&runner, player, StatusKind::Regeneration)
+            .unwrap()
+            .turns,
+        4
+    );
+
+    // Resting heals one more on top
+    runner.run_script(&[Command::Wait; 4]);
+    assert_eq!(hp(&runner, player), 5 + 4 * 3);
+    assert!(status(&runner, player, StatusKind::Regeneration).is_none());
+}
+
+#[test]
+fn a_paralyzed_player_loses_turns() {
+    let mut runner = arena();
+    let player = runner.player();
+    runner.submit(Command::Wait);
+    afflict(&mut runner, player, StatusKind::Paralysis, 3, 0);
+
+    let turn = runner.state.ecs.fetch::<GameClock>().turn;
+    runner.submit(Command::Move {
+        delta_x: 1,
+        delta_y: 0,
+    });
+    // The move, then two turns lost before the paralysis wears off
+    assert_eq!(runner.state.ecs.fetch::<GameClock>().turn, turn + 3);
+    assert!(runner.runstate() == RunState::AwaitingInput);
+    assert!(log_contains(&runner, "You are no longer paralyzed."));
+}
+
+#[test]
+fn a_paralyzed_monster_does_not_attack() {
+    let mut runner = arena();
+    let goblin = spawn_goblin(&mut runner.state.ecs, 11, 10, 100, 4);
+    runner.submit(Command::Wait);
+    afflict(&mut runner, goblin, StatusKind::Paralysis, 3, 0);
+
+    let before = attacks_by(&runner, "Goblin");
+    runner.run_script(&[Command::Wait; 3]);
+    assert_eq!(attacks_by(&runner, "Goblin"), before);
+    assert!(log_contains(&runner, "Goblin is no longer paralyzed."));
+    runner.submit(Command::Wait);
+    assert_eq!(attacks_by(&runner, "Goblin"), before + 1);
+}
+
+#[test]
+fn blindness_shrinks_sight_until_it_wears_off() {
+    let mut runner = arena();
+    let player = runner.player();
+    runner.submit(Command::Wait);
+    let seen = |runner: &HeadlessRunner| {
+        runner
+            .state
+            .ecs
+            .read_storage::<Viewshed>()
+            .get(runner.player())
+            .unwrap()
+            .visible_tiles
+            .len()
+    };
+    afflict(&mut runner, player, StatusKind::Blindness, 2, 0);
+    runner.submit(Command::Wait);
+    assert!(seen(&runner) <= 9);
+    runner.submit(Command::Wait);
+    runner.submit(Command::Wait);
+    assert!(seen(&runner) > 9);
+}
+
+#[test]
+fn effects_on_other_levels_wait_for_the_player() {
+    let mut runner = arena();
+    let goblin = spawn_goblin(&mut runner.state.ecs, 30, 30, 10, 1);
+    runner.submit(Command::Wait);
+    afflict(&mut runner, goblin, StatusKind::Poison, 5, 2);
+    let before = hp(&runner, goblin);
+
+    dungeon::freeze_level_entities(&mut runner.state.ecs);
+    // Not even a turn left over from before the level was left wakes it
+    runner
+        .state
+        .ecs
+        .write_storage::<MyTurn>()
+        .insert(goblin, MyTurn {})
+        .expect("Unable to insert turn");
+    *runner.state.ecs.write_resource::<RunState>() = RunState::MonsterTurn;
+    runner.state.advance(RunState::MonsterTurn);
+
+    assert_eq!(hp(&runner, goblin), before);
+    assert_eq!(
+        status(&runner, goblin, StatusKind::Poison).unwrap().turns,
+        5
+    );
+}
+
+#[test]
+fn a_confused_player_stumbles_about() {
+    let mut runner = arena();
+    let player = runner.player();
+    runner.submit(Command::Wait);
+    afflict(&mut runner, player, StatusKind::Confusion, 20, 0);
+    let mut directions = std::collections::HashSet::new();
+    for _ in 0..20 {
+        let before = *runner.state.ecs.fetch::<Point>();
+        runner.submit(Command::Move {
+            delta_x: 1,
+            delta_y: 0,
+        });
+        let after = *runner.state.ecs.fetch::<Point>();
+        directions.insert((after.x - before.x, after.y - before.y));
+    }
+    assert!(directions.len() > 1);
+    assert!(status(&runner, player, StatusKind::Confusion).is_none());
+}
+
+#[test]
+fn a_fast_monster_attacks_twice_a_turn() {
+    let mut runner = arena();
+    let goblin = spawn_goblin(&mut runner.state.ecs, 11, 10, 100, 4);
+    set_speed(&mut runner, goblin, NORMAL_SPEED * 2);
+    runner.submit(Command::Wait);
+
+    let before = attacks_by(&runner, "Goblin");
+    runner.submit(Command::Wait);
+    assert_eq!(attacks_by(&runner, "Goblin"), before + 2);
+    assert_eq!(runner.state.ecs.fetch::<GameClock>().turn, 3);
+}
+
+/// Spawns a feeble goblin worth `xp` next to the player and kills it.
+fn kill_goblin_worth(runner: &mut HeadlessRunner, xp: i32) -> RunState {
+    let goblin = spawn_goblin(&mut runner.state.ecs, 11, 10, 1, 0);
+    runner
+        .state
+        .ecs
+        .write_storage::<ExperienceValue>()
+        .insert(goblin, ExperienceValue { xp })
+        .expect("Unable to insert experience");
+    runner.submit(Command::Wait);
+    attack_until_dead(runner, goblin, 20)
+}
+
+fn experience(runner: &HeadlessRunner) -> Experience {
+    let experience = runner.state.ecs.read_storage::<Experience>();
+    experience.get(runner.player()).unwrap().clone()
+}
+
+#[test]
+fn kills_award_experience_to_the_killer() {
+    let mut runner = arena();
+    assert!(kill_goblin_worth(&mut runner, 35) == RunState::AwaitingInput);
+    assert_eq!(experience(&runner).xp, 35);
+    assert_eq!(experience(&runner).level, 1);
+    assert!(log_contains(&runner, "You gain 35 experience."));
+}
+
+#[test]
+fn a_new_level_waits_for_the_player_to_choose() {
+    let mut runner = arena();
+    let player = runner.player();
+    let melee = runner
+        .state
+        .ecs
+        .read_storage::<Skills>()
+        .get(player)
+        .unwrap()
+        .melee;
+    let needed = xp_to_next_level(1);
+    assert!(kill_goblin_worth(&mut runner, needed + 5) == RunState::LevelUp);
+
+    let turn = runner.state.ecs.fetch::<GameClock>().turn;
+    assert!(runner.submit(Command::Wait) == RunState::LevelUp);
+    assert_eq!(runner.state.ecs.fetch::<GameClock>().turn, turn);
+
+    let end = runner.submit(Command::LevelUp {
+        choice: LevelUpChoice::Skill(Skill::Melee),
+    });
+    assert!(end == RunState::AwaitingInput);
+    assert_eq!(experience(&runner).level, 2);
+    assert_eq!(experience(&runner).xp, 5);
+    let skills = runner.state.ecs.read_storage::<Skills>();
+    assert_eq!(skills.get(player).unwrap().melee, melee + 1);
+    let attributes = runner.state.ecs.read_storage::<Attributes>();
+    let stats = runner.state.ecs.read_storage::<CombatStats>();
+    let stats = stats.get(player).unwrap();
+    assert_eq!(
+        stats.max_hp,
+        attributes::max_hp(attributes.get(player).unwrap(), 2)
+    );
+    assert_eq!(stats.hp, stats.max_hp);
+}
+
+#[test]
+fn starving_player_takes_damage() {
+    let mut runner = arena();
+    let player = runner.player();
+    {
+        let mut clocks = runner.state.ecs.write_storage::<HungerClock>();
+        let clock = clocks.get_mut(player).unwrap();
+        clock.state = HungerState::Starving;
+        clock.duration = 0;
+    }
+
+    let before = hp(&runner, player);
+    runner.run_script(&[Command::Wait; 5]);
+
+    assert_eq!(hp(&runner, player), before - 5);
+    assert!(log_contains(&runner, "You are your own sustenance now"));
+}
+
+#[test]
+fn hunger_state_decays_over_time() {
+    let mut runner = arena();
+    let player = runner.player();
+    runner.run_script(&[Command::Wait; 25]);
+
+    let clocks = runner.state.ecs.read_storage::<HungerClock>();
+    assert!(clocks.get(player).unwrap().state == HungerState::Normal);
+}
+
+fn spawn_smack_trap(ecs: &mut World, x: i32, y: i32, damage_type: DamageType) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Name {
+            name: "Smack Trap".to_string(),
+        })
+        .with(Hidden {})
+        .with(EntryTrigger {})
+        .with(InflictsDamage {
+            damage: 6,
+            damage_type,
+        })
+        .with(SingleActivation {})
+        .build()
+}
+
+#[test]
+fn stepping_on_trap_hurts_and_consumes_it() {
+    let mut runner = arena();
+    let trap = spawn_smack_trap(&mut runner.state.ecs, 11, 10, DamageType::Blunt);
+    runner.submit(Command::Wait);
+
+    let player = runner.player();
+    let before = hp(&runner, player);
+    runner.submit(ATTACK_EAST);
+
+    assert_eq!(hp(&runner, player), before - 6);
+    assert!(!runner.state.ecs.is_alive(trap));
+    assert!(log_contains(&runner, "Smack Trap triggers!"));
+}
+
+/// How much a 6 hp blunt trap hurts the player once `setup` has had its way with them.
+fn blunt_trap_damage(setup: impl FnOnce(&mut World, Entity)) -> (i32, HeadlessRunner) {
+    let mut runner = arena();
+    let player = runner.player();
+    setup(&mut runner.state.ecs, player);
+    spawn_smack_trap(&mut runner.state.ecs, 11, 10, DamageType::Blunt);
+    runner.submit(Command::Wait);
+
+    let before = hp(&runner, player);
+    runner.submit(ATTACK_EAST);
+    (before - hp(&runner, player), runner)
+}
+
+#[test]
+fn resistances_change_damage_of_their_type() {
+    let (damage, runner) = blunt_trap_damage(|ecs, player| {
+        let damage_types = vec![DamageType::Blunt];
+        ecs.write_storage::<Resistant>()
+            .insert(player, Resistant { damage_types })
+            .expect("Unable to insert resistance");
+    });
+    assert_eq!(damage, 3);
+    assert!(log_contains(
+        &runner,
+        "Player resisted the blunt damage, taking 3 hp"
+    ));
+
+    let (damage, runner) = blunt_trap_damage(|ecs, player| {
+        let damage_types = vec![DamageType::Cold, DamageType::Blunt];
+        ecs.write_storage::<Immune>()
+            .insert(player, Immune { damage_types })
+            .expect("Unable to insert immunity");
+    });
+    assert_eq!(damage, 0);
+    assert!(log_contains(
+        &runner,
+        "Player resisted all of the blunt damage"
+    ));
+
+    let (damage, runner) = blunt_trap_damage(|ecs, player| {
+        let damage_types = vec![DamageType::Blunt];
+        ecs.write_storage::<Vulnerable>()
+            .insert(player, Vulnerable { damage_types })
+            .expect("Unable to insert vulnerability");
+    });
+    assert_eq!(damage, 12);
+    assert!(log_contains(
+        &runner,
+        "Player is vulnerable to blunt, taking 12 hp!"
+    ));
+
+    let (damage, runner) = blunt_trap_damage(|ecs, player| {
+        let damage_types = vec![DamageType::Fire];
+        ecs.write_storage::<Resistant>()
+            .insert(player, Resistant { damage_types })
+            .expect("Unable to insert resistance");
+    });
+    assert_eq!(damage, 6);
+    assert!(!log_contains(&runner, "resisted"));
+}
+
+#[test]
+fn lethal_damage_ends_the_game() {
+    let mut runner = arena();
+    let player = runner.player();
+    runner
+        .state
+        .ecs
+        .write_storage::<CombatStats>()
+        .get_mut(player)
+        .unwrap()
+        .hp = 1;
+    spawn_goblin(&mut runner.state.ecs, 11, 10, 100, 10);
+
+    let end = runner.run_script(&[Command::Wait; 10]);
+
+    assert!(end == RunState::GameOver);
+}
+
+#[test]
+fn same_seed_reproduces_the_run() {
+    fn snapshot(runner: &HeadlessRunner) -> (Vec<TileType>, Vec<(String, i32, i32)>) {
+        let ecs = &runner.state.ecs;
+        let tiles = ecs.fetch::<Map>().tiles.clone();
+        let spawns = (&ecs.read_storage::<Name>(), &ecs.read_storage::<Position>())
+            .join()
+            .map(|(name, pos)| (name.name.clone(), pos.x, pos.y))
+            .collect();
+        (tiles, spawns)
+    }
+
+    let script = [
+        Command::Wait,
+        Command::Move {
+            delta_x: 1,
+            delta_y: 0,
+        },
+        Command::Wait,
+    ];
+    let mut first = HeadlessRunner::new(42);
+    let mut second = HeadlessRunner::new(42);
+    assert!(snapshot(&first) == snapshot(&second));
+
+    first.run_script(&script);
+    second.run_script(&script);
+    assert!(snapshot(&first) == snapshot(&second));
+    assert!(first.state.ecs.fetch::<GameLog>().entries().eq(second
+        .state
+        .ecs
+        .fetch::<GameLog>()
+        .entries()));
+
+    let other = HeadlessRunner::new(43);
+    assert!(snapshot(&first).0 != snapshot(&other).0);
+}
@@ -0,0 +1,242 @@
+use hello_rltk::map_builders::prefab::{Prefab, LEVELS, VAULTS};
+use hello_rltk::map_builders::{all_builders, all_vault_builders, MapBuilder};
+use hello_rltk::raws::RawMaster;
+use hello_rltk::{Map, TileType};
+use rltk::rex::XpFile;
+use rltk::RandomNumberGenerator;
+
+/// Dijkstra distance from `start_idx` to every tile; unreachable tiles stay at `f32::MAX`.
+fn distances_from(map: &mut Map, start_idx: usize) -> Vec<f32> {
+    map.populate_blocked();
+    rltk::DijkstraMap::new(map.width, map.height, &[start_idx], map, 10_000.0).map
+}
+
+#[test]
+fn every_builder_starts_on_floor_with_reachable_stairs() {
+    for seed in 1..=10 {
+        for depth in &[1, 2, 5] {
+            for mut builder in all_builders(*depth, 80, 43) {
+                let mut rng = RandomNumberGenerator::seeded(seed);
+                builder.build_map(&mut rng);
+                let mut map = builder.get_map();
+                let start = builder.get_starting_position();
+                let start_idx = map.xy_idx(start.x, start.y);
+
+                assert_eq!(map.depth, *depth);
+                assert_eq!(map.tiles[start_idx], TileType::Floor, "seed {}", seed);
+
+                let distances = distances_from(&mut map, start_idx);
+                let stairs: Vec<usize> = map
+                    .tiles
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, tile)| **tile == TileType::DownStairs)
+                    .map(|(i, _)| i)
+                    .collect();
+                assert_eq!(stairs.len(), 1, "seed {} depth {}", seed, depth);
+                assert!(
+                    distances[stairs[0]] < f32::MAX,
+                    "stairs unreachable, seed {} depth {}",
+                    seed,
+                    depth
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn room_builders_still_make_a_level_when_no_room_fits() {
+    for (name, mut builder) in ["simple", "bsp"].iter().zip(all_builders(1, 10, 10)) {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        builder.build_map(&mut rng);
+        let mut map = builder.get_map();
+        let start = builder.get_starting_position();
+        let start_idx = map.xy_idx(start.x, start.y);
+        assert_eq!(map.tiles[start_idx], TileType::Floor, "{}", name);
+
+        let distances = distances_from(&mut map, start_idx);
+        let stairs = map
+            .tiles
+            .iter()
+            .position(|tile| *tile == TileType::DownStairs)
+            .unwrap();
+        assert!(distances[stairs] < f32::MAX, "{}", name);
+    }
+}
+
+#[test]
+fn spawn_regions_avoid_the_start_and_walls() {
+    for seed in 1..=10 {
+        for mut builder in all_builders(3, 80, 43) {
+            let mut rng = RandomNumberGenerator::seeded(seed);
+            builder.build_map(&mut rng);
+            let map = builder.get_map();
+            let start = builder.get_starting_position();
+            let start_idx = map.xy_idx(start.x, start.y);
+
+            let regions = builder.get_spawn_regions();
+            assert!(
+                !regions.is_empty() || !builder.get_spawn_list().is_empty(),
+                "seed {}",
+                seed
+            );
+            for idx in regions.iter().flatten() {
+                assert_ne!(*idx, start_idx);
+                assert_ne!(map.tiles[*idx], TileType::Wall);
+            }
+        }
+    }
+}
+
+/// Every tile that isn't wall can be walked to from the start.
+fn assert_fully_connected(builder: &dyn MapBuilder, context: &str) {
+    let mut map = builder.get_map();
+    let start = builder.get_starting_position();
+    let start_idx = map.xy_idx(start.x, start.y);
+    let distances = distances_from(&mut map, start_idx);
+    for (i, tile) in map.tiles.iter().enumerate() {
+        if *tile != TileType::Wall {
+            assert!(
+                distances[i] < f32::MAX,
+                "{}: tile {} unreachable",
+                context,
+                i
+            );
+        }
+    }
+}
+
+#[test]
+fn vaults_are_stamped_without_cutting_the_level_off() {
+    let mut stamped = 0;
+    for seed in 1..=10 {
+        for mut builder in all_vault_builders(5, 80, 43) {
+            let mut rng = RandomNumberGenerator::seeded(seed);
+            builder.build_map(&mut rng);
+            assert_fully_connected(builder.as_ref(), &format!("seed {}", seed));
+
+            let spawn_list = builder.get_spawn_list();
+            if !spawn_list.is_empty() {
+                stamped += 1;
+            }
+            for (idx, _) in spawn_list.iter() {
+                for region in builder.get_spawn_regions().iter() {
+                    assert!(!region.contains(idx), "random spawn inside a vault");
+                }
+            }
+        }
+    }
+    assert!(stamped > 0, "no vault ever fit");
+}
+
+#[test]
+fn no_vaults_before_their_depth() {
+    let min_depth = VAULTS.iter().map(|vault| vault.min_depth).min().unwrap();
+    for mut builder in all_vault_builders(min_depth - 1, 80, 43) {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        builder.build_map(&mut rng);
+        assert!(builder.get_spawn_list().is_empty());
+    }
+}
+
+#[test]
+fn bundled_prefabs_only_use_known_glyphs_and_entities() {
+    let raws = RawMaster::load();
+    let paths = LEVELS.iter().chain(VAULTS.iter().map(|vault| &vault.path));
+    for path in paths {
+        let prefab = Prefab::load(path);
+        prefab.check().unwrap_or_else(|e| panic!("{}: {}", path, e));
+        for y in 0..prefab.height {
+            for x in 0..prefab.width {
+                if let Some(name) = prefab.cell(x, y).spawn {
+                    assert!(raws.get(name).is_some(), "{}: no entity {}", path, name);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn levels_have_one_start_and_stairs() {
+    for path in LEVELS.iter() {
+        let prefab = Prefab::load(path);
+        let mut starts = 0;
+        let mut stairs = 0;
+        for y in 0..prefab.height {
+            for x in 0..prefab.width {
+                let cell = prefab.cell(x, y);
+                if cell.player_start {
+                    starts += 1;
+                }
+                if cell.tile == TileType::DownStairs {
+                    stairs += 1;
+                }
+            }
+        }
+        assert_eq!((starts, stairs), (1, 1), "{}", path);
+    }
+}
+
+#[test]
+fn upper_layers_draw_over_lower_ones_except_where_transparent() {
+    let bytes = std::fs::read("resources/mltest.xp").unwrap();
+    let xp = XpFile::read(&mut &bytes[..]).unwrap();
+    let prefab = Prefab::from_xp(&xp);
+
+    assert_eq!((prefab.width, prefab.height), (8, 4));
+    assert_eq!(prefab.glyph(0, 0), 'A');
+    assert_eq!(prefab.glyph(2, 1), 'B');
+    assert_eq!(prefab.glyph(5, 2), 'B');
+    assert_eq!(prefab.glyph(6, 2), 'A');
+    assert_eq!(prefab.glyph(3, 3), 'A');
+    assert!(prefab.check().is_err());
+}
+
+#[test]
+fn every_builder_leaves_room_to_play() {
+    for seed in 1..=5 {
+        for mut builder in all_builders(4, 80, 43) {
+            let mut rng = RandomNumberGenerator::seeded(seed);
+            builder.build_map(&mut rng);
+            let map = builder.get_map();
+            let open = map.tiles.iter().filter(|t| **t != TileType::Wall).count();
+            assert!(
+                open * 10 > map.tiles.len(),
+                "only {} open tiles, seed {}",
+                open,
+                seed
+            );
+        }
+    }
+}
+
+#[test]
+fn builders_fill_maps_of_any_size() {
+    for (width, height) in &[(60, 30), (80, 43), (140, 90)] {
+        for mut builder in all_vault_builders(4, *width, *height) {
+            let mut rng = RandomNumberGenerator::seeded(3);
+            builder.build_map(&mut rng);
+            let map = builder.get_map();
+            let context = format!("{}x{}", width, height);
+
+            assert_eq!((map.width, map.height), (*width, *height));
+            assert_eq!(map.tiles.len(), (*width * *height) as usize);
+            for (i, tile) in map.tiles.iter().enumerate() {
+                let (x, y) = (i as i32 % width, i as i32 / width);
+                if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
+                    assert_eq!(
+                        *tile,
+                        TileType::Wall,
+                        "{}: open edge at ({}, {})",
+                        context,
+                        x,
+                        y
+                    );
+                }
+            }
+            assert_fully_connected(builder.as_ref(), &context);
+            assert!(map.tiles.contains(&TileType::DownStairs), "{}", context);
+        }
+    }
+}
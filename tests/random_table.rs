@@ -0,0 +1,108 @@
+use hello_rltk::random_table::RandomTable;
+use hello_rltk::raws::RawMaster;
+use rltk::RandomNumberGenerator;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+const ROLLS: usize = 200_000;
+
+/// Upper bound of the chi-squared distribution at p = 0.001, using the Wilson-Hilferty
+/// approximation so any number of degrees of freedom can be checked.
+fn chi_squared_critical(degrees_of_freedom: usize) -> f64 {
+    let k = degrees_of_freedom as f64;
+    let z = 3.09;
+    k * (1.0 - 2.0 / (9.0 * k) + z * (2.0 / (9.0 * k)).sqrt()).powi(3)
+}
+
+/// Rolls `table` many times with a fixed seed and checks the observed counts against the
+/// table's own weights with a chi-squared goodness-of-fit test.
+fn assert_matches_weights<T: Eq + Hash + std::fmt::Debug>(table: &RandomTable<T>, seed: u64) {
+    let mut rng = RandomNumberGenerator::seeded(seed);
+    let mut observed: HashMap<&T, usize> = HashMap::new();
+    for _ in 0..ROLLS {
+        let key = table.roll(&mut rng).expect("Table is not empty");
+        *observed.entry(key).or_insert(0) += 1;
+    }
+
+    let total = table.total_weight() as f64;
+    let mut chi_squared = 0.0;
+    for (key, weight) in table.weights() {
+        let expected = ROLLS as f64 * weight as f64 / total;
+        let seen = *observed.get(key).unwrap_or(&0) as f64;
+        chi_squared += (seen - expected).powi(2) / expected;
+    }
+
+    let weights = table.weights();
+    if weights.len() > 1 {
+        let critical = chi_squared_critical(weights.len() - 1);
+        assert!(
+            chi_squared < critical,
+            "chi-squared {} exceeds {} for {:?}, observed {:?}",
+            chi_squared,
+            critical,
+            weights,
+            observed
+        );
+    }
+    assert_eq!(
+        observed.len(),
+        weights.len(),
+        "every entry should be rolled"
+    );
+}
+
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+enum Loot {
+    Gold,
+    Gem,
+    Relic,
+}
+
+#[test]
+fn empty_table_rolls_nothing() {
+    let table: RandomTable<Loot> = RandomTable::new();
+    let mut rng = RandomNumberGenerator::seeded(1);
+    assert_eq!(table.roll(&mut rng), None);
+}
+
+#[test]
+fn single_entry_is_always_rolled() {
+    let table = RandomTable::new().add(Loot::Gold, 1);
+    let mut rng = RandomNumberGenerator::seeded(1);
+    for _ in 0..1000 {
+        assert_eq!(table.roll(&mut rng), Some(&Loot::Gold));
+    }
+}
+
+#[test]
+fn non_positive_weights_are_never_rolled() {
+    let table = RandomTable::new()
+        .add(Loot::Gold, 3)
+        .add(Loot::Gem, 0)
+        .add(Loot::Relic, -2);
+    assert_eq!(table.total_weight(), 3);
+    assert_eq!(table.weights(), vec![(&Loot::Gold, 3)]);
+}
+
+#[test]
+fn first_entry_gets_its_full_share() {
+    let table = RandomTable::new().add(Loot::Gold, 1).add(Loot::Gem, 1);
+    assert_matches_weights(&table, 7);
+}
+
+#[test]
+fn skewed_weights_match_distribution() {
+    let table = RandomTable::new()
+        .add(Loot::Gold, 70)
+        .add(Loot::Gem, 25)
+        .add(Loot::Relic, 5);
+    assert_matches_weights(&table, 11);
+}
+
+#[test]
+fn spawn_tables_match_their_weights() {
+    let raws = RawMaster::load();
+    for depth in &[1, 2, 5, 10] {
+        assert_matches_weights(&raws.spawn_table(*depth), *depth as u64);
+    }
+}
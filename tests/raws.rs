@@ -0,0 +1,257 @@
+use hello_rltk::attributes;
+use hello_rltk::headless::HeadlessRunner;
+use hello_rltk::initiative_system::NORMAL_SPEED;
+use hello_rltk::raws::{self, RawMaster};
+use hello_rltk::{
+    Attributes, CombatStats, DamageType, Energy, Immune, InflictsDamage, InflictsStatus, Name,
+    Resistant, Skills, StatusKind, Vulnerable,
+};
+use specs::prelude::*;
+
+#[test]
+fn bundled_raws_load() {
+    let raws = RawMaster::load();
+    assert!(raws.get("Goblin").is_some());
+    assert!(raws.get("Magic Missile Scroll").is_some());
+}
+
+#[test]
+fn unknown_fields_are_rejected() {
+    let json = r#"{
+        "entities": [ { "name": "Rat", "combat_stat": { "max_hp": 1, "defense": 0, "power": 1 } } ],
+        "spawn_table": []
+    }"#;
+    assert!(RawMaster::from_json(json).is_err());
+}
+
+#[test]
+fn bad_glyphs_and_colours_are_rejected() {
+    let glyph = r##"{
+        "entities": [ { "name": "Rat", "renderable": { "glyph": "rr", "fg": "#FFFFFF", "bg": "#000000", "order": 0 } } ],
+        "spawn_table": []
+    }"##;
+    let colour = r##"{
+        "entities": [ { "name": "Rat", "renderable": { "glyph": "r", "fg": "white", "bg": "#000000", "order": 0 } } ],
+        "spawn_table": []
+    }"##;
+    assert!(RawMaster::from_json(glyph).is_err());
+    assert!(RawMaster::from_json(colour).is_err());
+}
+
+#[test]
+fn duplicate_names_are_rejected() {
+    let json = r#"{
+        "entities": [ { "name": "Rat" }, { "name": "Rat" } ],
+        "spawn_table": []
+    }"#;
+    assert!(RawMaster::from_json(json).is_err());
+}
+
+#[test]
+fn spawn_table_names_must_match_an_entity() {
+    let json = r#"{
+        "entities": [ { "name": "Magic Missile Scroll" } ],
+        "spawn_table": [ { "name": "Magic Missle Scroll", "weight": 4 } ]
+    }"#;
+    let err = RawMaster::from_json(json).err().unwrap();
+    assert!(err.contains("Magic Missle Scroll"));
+}
+
+#[test]
+fn entities_that_can_never_spawn_are_reported() {
+    let json = r#"{
+        "entities": [
+            { "name": "Rat" },
+            { "name": "Ghost" },
+            { "name": "Dragon" },
+            { "name": "Bat" },
+            { "name": "Lich" }
+        ],
+        "spawn_table": [
+            { "name": "Rat", "weight": 1 },
+            { "name": "Dragon", "weight": -5, "weight_per_depth": 1 },
+            { "name": "Bat", "weight": 0 },
+            { "name": "Lich", "weight": -5, "weight_per_depth": 1, "max_depth": 5 }
+        ]
+    }"#;
+    let raws = RawMaster::from_json(json).unwrap();
+    assert_eq!(raws.unreachable(), vec!["Ghost", "Bat", "Lich"]);
+}
+
+#[test]
+fn bundled_raws_have_no_unreachable_entities() {
+    assert!(RawMaster::load().unreachable().is_empty());
+}
+
+#[test]
+fn every_spawn_table_entry_spawns_at_every_depth() {
+    let mut runner = HeadlessRunner::new(1);
+    let ecs = &mut runner.state.ecs;
+    for depth in 1..=20 {
+        let table = ecs.fetch::<RawMaster>().spawn_table(depth);
+        assert!(!table.weights().is_empty());
+        for (key, _weight) in table.weights() {
+            let name = ecs.fetch::<RawMaster>().def(*key).name.clone();
+            let entity = raws::spawn_entity(ecs, *key, 1, 1);
+            let names = ecs.read_storage::<Name>();
+            assert_eq!(names.get(entity).unwrap().name, name);
+        }
+    }
+}
+
+#[test]
+fn monsters_get_a_speed() {
+    let json = r#"{
+        "entities": [
+            { "name": "Rat", "monster": true },
+            { "name": "Wolf", "monster": true, "speed": 200 },
+            { "name": "Rock" }
+        ],
+        "spawn_table": []
+    }"#;
+    let mut runner = HeadlessRunner::new(1);
+    let ecs = &mut runner.state.ecs;
+    ecs.insert(RawMaster::from_json(json).unwrap());
+    let speed = |ecs: &mut World, name: &str| {
+        let entity = raws::spawn_named(ecs, name, 1, 1).unwrap();
+        let energy = ecs.read_storage::<Energy>();
+        energy.get(entity).map(|energy| energy.speed)
+    };
+    assert_eq!(speed(ecs, "Rat"), Some(NORMAL_SPEED));
+    assert_eq!(speed(ecs, "Wolf"), Some(200));
+    assert_eq!(speed(ecs, "Rock"), None);
+}
+
+#[test]
+fn fighters_derive_their_hit_points_from_attributes() {
+    let json = r#"{
+        "entities": [
+            { "name": "Rat", "attributes": { "toughness": 4 }, "skills": { "stealth": 2 } },
+            { "name": "Bat", "attributes": {} }
+        ],
+        "spawn_table": []
+    }"#;
+    let mut runner = HeadlessRunner::new(1);
+    let ecs = &mut runner.state.ecs;
+    ecs.insert(RawMaster::from_json(json).unwrap());
+
+    let rat = raws::spawn_named(ecs, "Rat", 1, 1).unwrap();
+    let attributes = ecs.read_storage::<Attributes>().get(rat).unwrap().clone();
+    assert_eq!(attributes.toughness, 4);
+    assert_eq!(attributes.might, 10);
+    assert_eq!(ecs.read_storage::<Skills>().get(rat).unwrap().stealth, 2);
+    let stats = ecs.read_storage::<CombatStats>().get(rat).unwrap().clone();
+    assert_eq!(stats.max_hp, attributes::max_hp(&attributes, 1));
+    assert_eq!(stats.hp, stats.max_hp);
+
+    let bat = raws::spawn_named(ecs, "Bat", 1, 1).unwrap();
+    assert!(ecs.read_storage::<Skills>().get(bat).unwrap() == &Skills::default());
+}
+
+#[test]
+fn weapons_need_valid_damage_dice() {
+    let json = |damage: &str| {
+        format!(
+            r#"{{
+                "entities": [ {{ "name": "Club", "melee_weapon": {{ "damage": "{}", "damage_type": "Blunt" }} }} ],
+                "spawn_table": []
+            }}"#,
+            damage
+        )
+    };
+    assert!(RawMaster::from_json(&json("1d6+1")).is_ok());
+    assert!(RawMaster::from_json(&json("heavy")).is_err());
+    assert!(RawMaster::from_json(&json("1d0")).is_err());
+}
+
+#[test]
+fn damage_and_resistances_are_typed() {
+    let json = r#"{
+        "entities": [
+            { "name": "Ember", "inflicts_damage": { "amount": 3, "damage_type": "Fire" } },
+            {
+                "name": "Yeti",
+                "resistances": ["Cold"],
+                "immunities": ["Poison", "Cold"],
+                "vulnerabilities": ["Fire"]
+            },
+            { "name": "Rock" }
+        ],
+        "spawn_table": []
+    }"#;
+    let mut runner = HeadlessRunner::new(1);
+    let ecs = &mut runner.state.ecs;
+    ecs.insert(RawMaster::from_json(json).unwrap());
+
+    let ember = raws::spawn_named(ecs, "Ember", 1, 1).unwrap();
+    let damage = ecs
+        .read_storage::<InflictsDamage>()
+        .get(ember)
+        .unwrap()
+        .clone();
+    assert_eq!(damage.damage, 3);
+    assert_eq!(damage.damage_type, DamageType::Fire);
+
+    let yeti = raws::spawn_named(ecs, "Yeti", 1, 1).unwrap();
+    let resistant = ecs.read_storage::<Resistant>();
+    assert_eq!(
+        resistant.get(yeti).unwrap().damage_types,
+        [DamageType::Cold]
+    );
+    let immune = ecs.read_storage::<Immune>();
+    assert_eq!(
+        immune.get(yeti).unwrap().damage_types,
+        [DamageType::Poison, DamageType::Cold]
+    );
+    let vulnerable = ecs.read_storage::<Vulnerable>();
+    assert_eq!(
+        vulnerable.get(yeti).unwrap().damage_types,
+        [DamageType::Fire]
+    );
+    drop((resistant, immune, vulnerable));
+
+    let rock = raws::spawn_named(ecs, "Rock", 1, 1).unwrap();
+    assert!(!ecs.read_storage::<Resistant>().contains(rock));
+    assert!(!ecs.read_storage::<Immune>().contains(rock));
+    assert!(!ecs.read_storage::<Vulnerable>().contains(rock));
+}
+
+#[test]
+fn unknown_damage_types_are_rejected() {
+    let json = r#"{
+        "entities": [ { "name": "Rat", "resistances": ["Lightning"] } ],
+        "spawn_table": []
+    }"#;
+    assert!(RawMaster::from_json(json).is_err());
+}
+
+#[test]
+fn skills_need_attributes() {
+    let json = r#"{
+        "entities": [ { "name": "Rat", "skills": { "melee": 1 } } ],
+        "spawn_table": []
+    }"#;
+    let err = RawMaster::from_json(json).err().unwrap();
+    assert!(err.contains("Rat"));
+}
+
+#[test]
+fn items_inflict_status_effects() {
+    let mut runner = HeadlessRunner::new(1);
+    let ecs = &mut runner.state.ecs;
+    let scroll = raws::spawn_named(ecs, "Confusion Scroll", 1, 1).unwrap();
+    let potion = raws::spawn_named(ecs, "Regeneration Potion", 1, 1).unwrap();
+    let inflicts = ecs.read_storage::<InflictsStatus>();
+    let effect = |entity: Entity| inflicts.get(entity).unwrap().effect;
+    assert_eq!(effect(scroll).kind, StatusKind::Confusion);
+    assert_eq!(effect(scroll).potency, 0);
+    assert_eq!(effect(potion).kind, StatusKind::Regeneration);
+    assert_eq!(effect(potion).potency, 2);
+
+    let json = r#"{
+        "entities": [ { "name": "Dud", "inflicts_status": { "kind": "Slow", "turns": 0 } } ],
+        "spawn_table": []
+    }"#;
+    let err = RawMaster::from_json(json).err().unwrap();
+    assert!(err.contains("Dud"));
+}
@@ -0,0 +1,901 @@
+use hello_rltk::attributes;
+use hello_rltk::game_clock::GameClock;
+use hello_rltk::headless::{Command, HeadlessRunner};
+use hello_rltk::saveload_system::{
+    format_timestamp, load_from_bytes, load_from_str, load_game, read_header, save_game,
+    save_to_bytes, save_to_string, SaveError, SaveFormat, SaveKind, SaveSlots, SlotStatus,
+    AUTOSAVE_INTERVAL, LEGACY_STORAGES, SAVE_SLOT_COUNT, SCHEMA_VERSION,
+};
+use hello_rltk::*;
+use rltk::Point;
+use serde_json::Value;
+use specs::prelude::*;
+use specs::saveload::{MarkedBuilder, SimpleMarker};
+
+fn player_position(runner: &HeadlessRunner) -> Point {
+    *runner.state.ecs.fetch::<Point>()
+}
+
+fn player_hp(runner: &HeadlessRunner) -> i32 {
+    let stats = runner.state.ecs.read_storage::<CombatStats>();
+    stats.get(runner.player()).unwrap().hp
+}
+
+fn decode_map_bits(map: &mut Value) {
+    for field in &["revealed_tiles", "visible_tiles", "blocked"] {
+        let runs: Vec<u32> = serde_json::from_value(map[*field].take()).unwrap();
+        map[*field] = serde_json::to_value(rle_bits::decode(&runs)).unwrap();
+    }
+}
+
+/// The current game as a schema 3 save, from before map bits were run-length encoded, the
+/// game clock replaced the turn count, and there was a turn schedule or experience.
+fn schema_3_save(runner: &mut HeadlessRunner) -> Value {
+    let mut save: Value =
+        serde_json::from_str(&save_to_string(&mut runner.state.ecs).unwrap()).unwrap();
+    save["header"]["schema_version"] = 3.into();
+    let storages = save["storages"].as_object_mut().unwrap();
+    // Power and defense were stored, not derived
+    let attributes = storages.remove("Attributes").unwrap();
+    let skills = storages.remove("Skills").unwrap();
+    for (i, entry) in storages["CombatStats"]
+        .as_array_mut()
+        .unwrap()
+        .iter_mut()
+        .enumerate()
+    {
+        let stats = &mut entry["components"][0];
+        if stats.is_null() {
+            continue;
+        }
+        let attributes: Attributes =
+            serde_json::from_value(attributes[i]["components"][0].clone()).unwrap();
+        let skills: Skills = serde_json::from_value(skills[i]["components"][0].clone()).unwrap();
+        stats["power"] = attributes::to_hit(&attributes, &skills).into();
+        stats["defense"] = attributes::dodge(&attributes, &skills).into();
+    }
+    // Weapons added their average damage
+    let mut weapons = storages.remove("MeleeWeapon").unwrap();
+    for entry in weapons.as_array_mut().unwrap() {
+        let weapon = &mut entry["components"][0];
+        if weapon.is_null() {
+            continue;
+        }
+        let weapon: MeleeWeapon = serde_json::from_value(weapon.take()).unwrap();
+        let dice = weapon.damage;
+        let power = dice.n_dice * (dice.die_type + 1) / 2 + dice.bonus;
+        entry["components"][0] = serde_json::json!({ "power": power });
+    }
+    storages.insert("MeleePowerBonus".to_string(), weapons);
+    for entry in storages["InflictsDamage"].as_array_mut().unwrap() {
+        if let Some(damage) = entry["components"][0].as_object_mut() {
+            damage.remove("damage_type");
+        }
+    }
+    // Confusion was the only status, on the scrolls that caused it and the confused alike
+    let inflicts = storages.remove("InflictsStatus").unwrap();
+    let mut confusion = storages.remove("StatusEffects").unwrap();
+    for (i, entry) in confusion.as_array_mut().unwrap().iter_mut().enumerate() {
+        let mut effects: Vec<StatusEffect> = match entry["components"][0].take() {
+            Value::Null => Vec::new(),
+            effects => {
+                serde_json::from_value::<StatusEffects>(effects)
+                    .unwrap()
+                    .effects
+            }
+        };
+        if let Value::Object(_) = inflicts[i]["components"][0] {
+            let inflicts: InflictsStatus =
+                serde_json::from_value(inflicts[i]["components"][0].clone()).unwrap();
+            effects.push(inflicts.effect);
+        }
+        if let Some(effect) = effects
+            .iter()
+            .find(|effect| effect.kind == StatusKind::Confusion)
+        {
+            entry["components"][0] = serde_json::json!({ "turns": effect.turns });
+        }
+    }
+    storages.insert("Confusion".to_string(), confusion);
+    for newer in &[
+        "Energy",
+        "MyTurn",
+        "Experience",
+        "ExperienceValue",
+        "Resistant",
+        "Immune",
+        "Vulnerable",
+    ] {
+        storages.remove(*newer);
+    }
+    for helper in save["storages"]["SerializationHelper"]
+        .as_array_mut()
+        .unwrap()
+    {
+        let helper = &mut helper["components"][0];
+        if helper.is_null() {
+            continue;
+        }
+        decode_map_bits(&mut helper["map"]);
+        let turns = helper["clock"]["turn"].take();
+        let fields = helper.as_object_mut().unwrap();
+        fields.remove("clock");
+        fields.remove("seed");
+        fields.insert("turns".to_string(), turns);
+        for map in helper["dungeon"]["maps"]
+            .as_object_mut()
+            .unwrap()
+            .values_mut()
+        {
+            decode_map_bits(map);
+        }
+    }
+    save
+}
+
+/// A save in the headerless format the game wrote before saves were versioned.
+fn legacy_save(runner: &mut HeadlessRunner, with_dungeon: bool) -> String {
+    let save = schema_3_save(runner);
+    let mut storages = save["storages"].as_object().unwrap().clone();
+    let mut names = LEGACY_STORAGES.to_vec();
+    for helper in storages["SerializationHelper"].as_array_mut().unwrap() {
+        if let Some(helper) = helper["components"][0].as_object_mut() {
+            helper.remove("turns");
+            if !with_dungeon {
+                helper.remove("dungeon");
+            }
+        }
+    }
+    if with_dungeon {
+        names.insert(1, "OtherLevelPosition");
+    }
+    names
+        .iter()
+        .map(|name| serde_json::to_string(&storages[*name]).unwrap())
+        .collect()
+}
+
+#[test]
+fn saves_carry_a_versioned_header() {
+    let mut runner = HeadlessRunner::new(5);
+    let save = save_to_string(&mut runner.state.ecs).unwrap();
+    let header = read_header(save.as_bytes()).unwrap();
+    assert_eq!(header.schema_version, SCHEMA_VERSION);
+    assert_eq!(header.game_version, env!("CARGO_PKG_VERSION"));
+}
+
+#[test]
+fn a_save_loads_back_into_another_game() {
+    let mut original = HeadlessRunner::new(5);
+    original.submit(Command::Wait);
+    let save = save_to_string(&mut original.state.ecs).unwrap();
+
+    let mut restored = HeadlessRunner::new(6);
+    load_from_str(&mut restored.state.ecs, &save).unwrap();
+
+    assert_eq!(player_position(&restored), player_position(&original));
+    assert_eq!(player_hp(&restored), player_hp(&original));
+    assert!(restored.state.ecs.fetch::<Map>().tiles == original.state.ecs.fetch::<Map>().tiles);
+    assert!(restored.submit(Command::Wait) == RunState::AwaitingInput);
+}
+
+#[test]
+fn saves_from_before_headers_are_upgraded() {
+    for &with_dungeon in &[false, true] {
+        let mut original = HeadlessRunner::new(9);
+        let save = legacy_save(&mut original, with_dungeon);
+        let expected_schema = if with_dungeon { 2 } else { 1 };
+        assert_eq!(
+            read_header(save.as_bytes()).unwrap().schema_version,
+            expected_schema
+        );
+
+        let mut restored = HeadlessRunner::new(10);
+        load_from_str(&mut restored.state.ecs, &save).unwrap();
+        assert_eq!(player_position(&restored), player_position(&original));
+        assert!(restored.state.ecs.fetch::<Map>().tiles == original.state.ecs.fetch::<Map>().tiles);
+        let player = *restored.state.ecs.fetch::<Entity>();
+        assert!(restored.state.ecs.read_storage::<Energy>().contains(player));
+        assert!(restored
+            .state
+            .ecs
+            .read_storage::<Experience>()
+            .contains(player));
+        let fighting = |runner: &HeadlessRunner, player: Entity| {
+            let attributes = runner.state.ecs.read_storage::<Attributes>();
+            let skills = runner.state.ecs.read_storage::<Skills>();
+            let (attributes, skills) =
+                (attributes.get(player).unwrap(), skills.get(player).unwrap());
+            (
+                attributes::to_hit(attributes, skills),
+                attributes::dodge(attributes, skills),
+            )
+        };
+        assert_eq!(
+            fighting(&restored, player),
+            fighting(&original, original.player())
+        );
+        assert!(restored.submit(Command::Wait) == RunState::AwaitingInput);
+    }
+}
+
+#[test]
+fn saves_with_plain_map_bits_are_upgraded() {
+    let mut original = HeadlessRunner::new(9);
+    original.submit(Command::Wait);
+    let save = schema_3_save(&mut original).to_string();
+
+    let mut restored = HeadlessRunner::new(10);
+    load_from_str(&mut restored.state.ecs, &save).unwrap();
+    let original_map = original.state.ecs.fetch::<Map>();
+    let restored_map = restored.state.ecs.fetch::<Map>();
+    assert_eq!(restored_map.revealed_tiles, original_map.revealed_tiles);
+    assert_eq!(restored_map.blocked, original_map.blocked);
+}
+
+#[test]
+fn damage_from_older_saves_gets_a_type() {
+    let mut original = HeadlessRunner::new(9);
+    for name in &[
+        "Fireball Scroll",
+        "Magic Missile Scroll",
+        "Smack Trap",
+        "Dagger",
+    ] {
+        raws::spawn_named(&mut original.state.ecs, name, 1, 1).unwrap();
+    }
+    let save = schema_3_save(&mut original).to_string();
+
+    let mut restored = HeadlessRunner::new(10);
+    load_from_str(&mut restored.state.ecs, &save).unwrap();
+    let ecs = &restored.state.ecs;
+    let names = ecs.read_storage::<Name>();
+    let mut typed = Vec::new();
+    for (name, damage) in (&names, &ecs.read_storage::<InflictsDamage>()).join() {
+        typed.push((name.name.clone(), damage.damage_type));
+    }
+    for (name, weapon) in (&names, &ecs.read_storage::<MeleeWeapon>()).join() {
+        typed.push((name.name.clone(), weapon.damage_type));
+    }
+    for expected in &[
+        ("Fireball Scroll", DamageType::Fire),
+        ("Magic Missile Scroll", DamageType::Piercing),
+        ("Smack Trap", DamageType::Blunt),
+        ("Dagger", DamageType::Slashing),
+    ] {
+        assert!(typed.contains(&(expected.0.to_string(), expected.1)));
+    }
+    for (name, damage_type) in typed {
+        match name.as_str() {
+            "Fireball Scroll" => assert_eq!(damage_type, DamageType::Fire),
+            "Smack Trap" => assert_eq!(damage_type, DamageType::Blunt),
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn confusion_from_older_saves_becomes_a_status_effect() {
+    let mut original = HeadlessRunner::new(9);
+    raws::spawn_named(&mut original.state.ecs, "Confusion Scroll", 1, 1).unwrap();
+    let player = original.player();
+    let confused = StatusEffect {
+        kind: StatusKind::Confusion,
+        turns: 3,
+        potency: 0,
+    };
+    original
+        .state
+        .ecs
+        .write_storage::<StatusEffects>()
+        .insert(
+            player,
+            StatusEffects {
+                effects: vec![confused],
+            },
+        )
+        .unwrap();
+    let save = schema_3_save(&mut original);
+    let confusion = save["storages"]["Confusion"].as_array().unwrap();
+    assert_eq!(
+        confusion
+            .iter()
+            .filter(|entry| !entry["components"][0].is_null())
+            .count(),
+        2
+    );
+
+    let mut restored = HeadlessRunner::new(10);
+    load_from_str(&mut restored.state.ecs, &save.to_string()).unwrap();
+    let ecs = &restored.state.ecs;
+    let player = *ecs.fetch::<Entity>();
+    assert_eq!(
+        ecs.read_storage::<StatusEffects>()
+            .get(player)
+            .unwrap()
+            .effects,
+        vec![confused]
+    );
+    let names = ecs.read_storage::<Name>();
+    let inflicts = ecs.read_storage::<InflictsStatus>();
+    let (_, inflicts) = (&names, &inflicts)
+        .join()
+        .find(|(name, _)| name.name == "Confusion Scroll")
+        .unwrap();
+    assert_eq!(inflicts.effect.kind, StatusKind::Confusion);
+    assert_eq!(inflicts.effect.turns, 4);
+}
+
+#[test]
+fn map_bits_are_saved_as_runs() {
+    assert_eq!(rle_bits::encode(&[]), vec![0]);
+    assert_eq!(rle_bits::encode(&[true, true, false]), vec![0, 2, 1]);
+    assert_eq!(rle_bits::encode(&[false, false, true]), vec![2, 1]);
+    for bits in &[
+        vec![],
+        vec![true],
+        vec![false, true, true, false, false, false, true],
+    ] {
+        assert_eq!(&rle_bits::decode(&rle_bits::encode(bits)), bits);
+    }
+
+    let mut runner = HeadlessRunner::new(5);
+    let save: Value =
+        serde_json::from_str(&save_to_string(&mut runner.state.ecs).unwrap()).unwrap();
+    let helper = save["storages"]["SerializationHelper"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|entry| !entry["components"][0].is_null())
+        .unwrap();
+    let tiles = runner.state.ecs.fetch::<Map>().tiles.len();
+    let revealed = helper["components"][0]["map"]["revealed_tiles"]
+        .as_array()
+        .unwrap();
+    assert!(revealed.len() < tiles / 4);
+}
+
+#[test]
+fn binary_saves_are_smaller_and_load_back() {
+    let mut original = HeadlessRunner::new(14);
+    original.submit(Command::Wait);
+    let binary = save_to_bytes(&mut original.state.ecs, SaveFormat::Binary).unwrap();
+    let json = save_to_bytes(&mut original.state.ecs, SaveFormat::Json).unwrap();
+    assert!(binary.len() < json.len());
+    assert_eq!(read_header(&binary).unwrap().schema_version, SCHEMA_VERSION);
+
+    for save in &[binary, json] {
+        let mut restored = HeadlessRunner::new(15);
+        load_from_bytes(&mut restored.state.ecs, save).unwrap();
+        assert_eq!(player_position(&restored), player_position(&original));
+        assert!(restored.state.ecs.fetch::<Map>().tiles == original.state.ecs.fetch::<Map>().tiles);
+        let player = *restored.state.ecs.fetch::<Entity>();
+        assert!(restored.state.ecs.read_storage::<Energy>().contains(player));
+        assert!(restored.submit(Command::Wait) == RunState::AwaitingInput);
+    }
+}
+
+#[test]
+fn saves_from_newer_versions_are_refused_without_touching_the_game() {
+    let mut runner = HeadlessRunner::new(5);
+    let mut save: Value =
+        serde_json::from_str(&save_to_string(&mut runner.state.ecs).unwrap()).unwrap();
+    save["header"]["schema_version"] = (SCHEMA_VERSION + 1).into();
+    let player = runner.player();
+
+    let err = load_from_str(&mut runner.state.ecs, &save.to_string()).unwrap_err();
+    assert!(matches!(err, SaveError::TooNew(_)));
+    assert!(err.to_string().contains("save format"));
+    assert!(runner.state.ecs.is_alive(player));
+}
+
+#[test]
+fn damaged_saves_are_errors_not_panics() {
+    let mut runner = HeadlessRunner::new(5);
+    let player = runner.player();
+    for data in &["", "not json", "[1, 2, 3]", "{\"header\": 4}"] {
+        assert!(load_from_str(&mut runner.state.ecs, data).is_err());
+    }
+    let truncated = save_to_string(&mut runner.state.ecs).unwrap();
+    let truncated = &truncated[..truncated.len() / 2];
+    assert!(matches!(
+        load_from_str(&mut runner.state.ecs, truncated),
+        Err(SaveError::Json(_))
+    ));
+    assert!(runner.state.ecs.is_alive(player));
+}
+
+#[test]
+fn maps_that_dont_fit_their_size_are_refused() {
+    let mut runner = HeadlessRunner::new(5);
+    let player = runner.player();
+    let mut save: Value =
+        serde_json::from_str(&save_to_string(&mut runner.state.ecs).unwrap()).unwrap();
+    let helper = save["storages"]["SerializationHelper"]
+        .as_array_mut()
+        .unwrap()
+        .iter_mut()
+        .find(|entry| !entry["components"][0].is_null())
+        .unwrap();
+    helper["components"][0]["map"]["revealed_tiles"]
+        .as_array_mut()
+        .unwrap()
+        .pop();
+
+    assert!(matches!(
+        load_from_str(&mut runner.state.ecs, &save.to_string()),
+        Err(SaveError::Corrupt(_))
+    ));
+    assert!(runner.state.ecs.is_alive(player));
+}
+
+fn temp_slots(name: &str) -> SaveSlots {
+    let dir = std::env::temp_dir().join(format!("hello-rltk-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    SaveSlots::in_dir(dir)
+}
+
+#[test]
+fn slots_list_a_summary_of_each_save() {
+    let slots = temp_slots("slot-summary");
+    let mut runner = HeadlessRunner::new(5);
+    runner.state.ecs.insert(slots.clone());
+    for _ in 0..3 {
+        runner.submit(Command::Wait);
+    }
+    save_game(&mut runner.state.ecs, 2, SaveKind::Manual).unwrap();
+
+    let listing = slots.list();
+    assert_eq!(listing.len(), SAVE_SLOT_COUNT);
+    assert_eq!(listing[0], SlotStatus::Empty);
+    match &listing[2] {
+        SlotStatus::Saved(Some(summary)) => {
+            assert_eq!(summary.name, "Player");
+            assert_eq!(summary.depth, 1);
+            assert_eq!(summary.hp, player_hp(&runner));
+            assert_eq!(summary.turn, 3);
+            assert!(summary.saved_at > 0);
+        }
+        other => panic!("expected a summary, got {:?}", other),
+    }
+    assert_eq!(slots.first_free(), Some(0));
+    let _ = std::fs::remove_dir_all(slots.path(0).parent().unwrap());
+}
+
+#[test]
+fn the_game_clock_advances_and_survives_a_save() {
+    let mut original = HeadlessRunner::new(5);
+    let start = *original.state.ecs.fetch::<GameClock>();
+    original.submit(Command::Wait);
+    original.submit(Command::Wait);
+    let clock = *original.state.ecs.fetch::<GameClock>();
+    assert_eq!(clock.turn, start.turn + 2);
+    assert!(clock.tick > start.tick);
+
+    let save = save_to_string(&mut original.state.ecs).unwrap();
+    let mut restored = HeadlessRunner::new(6);
+    load_from_str(&mut restored.state.ecs, &save).unwrap();
+    assert_eq!(*restored.state.ecs.fetch::<GameClock>(), clock);
+}
+
+#[test]
+fn the_run_seed_survives_a_save_and_reloads_play_out_the_same() {
+    let mut original = HeadlessRunner::new(5);
+    original.submit(Command::Wait);
+    let save = save_to_string(&mut original.state.ecs).unwrap();
+
+    let mut first = HeadlessRunner::new(6);
+    let mut second = HeadlessRunner::new(7);
+    load_from_str(&mut first.state.ecs, &save).unwrap();
+    load_from_str(&mut second.state.ecs, &save).unwrap();
+    assert_eq!(first.state.ecs.fetch::<RunSeed>().seed, 5);
+    assert_eq!(second.state.ecs.fetch::<RunSeed>().seed, 5);
+    let roll = |runner: &HeadlessRunner| {
+        runner
+            .state
+            .ecs
+            .write_resource::<rltk::RandomNumberGenerator>()
+            .next_u64()
+    };
+    assert_eq!(roll(&first), roll(&second));
+
+    // Saves from before the seed was kept carry on from seed 0
+    let older = schema_3_save(&mut original).to_string();
+    load_from_str(&mut first.state.ecs, &older).unwrap();
+    assert_eq!(first.state.ecs.fetch::<RunSeed>().seed, 0);
+}
+
+#[test]
+fn loading_a_slot_empties_it() {
+    let slots = temp_slots("slot-load");
+    let mut original = HeadlessRunner::new(5);
+    original.state.ecs.insert(slots.clone());
+    original.submit(Command::Wait);
+    original.submit(Command::Wait);
+    save_game(&mut original.state.ecs, 1, SaveKind::Manual).unwrap();
+    assert!(slots.is_used(1));
+
+    let mut restored = HeadlessRunner::new(6);
+    restored.state.ecs.insert(slots.clone());
+    load_game(&mut restored.state.ecs, 1).unwrap();
+    assert_eq!(player_position(&restored), player_position(&original));
+    assert_eq!(restored.state.ecs.fetch::<GameClock>().turn, 2);
+    assert_eq!(restored.state.ecs.fetch::<SaveSlots>().current, Some(1));
+    assert!(!slots.any_used());
+
+    assert!(matches!(
+        load_game(&mut restored.state.ecs, 1),
+        Err(SaveError::Io(_))
+    ));
+    let _ = std::fs::remove_dir_all(slots.path(0).parent().unwrap());
+}
+
+#[test]
+fn timestamps_read_as_utc_dates() {
+    assert_eq!(format_timestamp(0), "1970-01-01 00:00");
+    assert_eq!(format_timestamp(951_827_400), "2000-02-29 12:30");
+    assert_eq!(format_timestamp(1_700_000_000), "2023-11-14 22:13");
+}
+
+/// Names of the structs `components.rs` derives `Component` for.
+fn declared_components() -> Vec<String> {
+    let source = include_str!("../src/components.rs");
+    let mut declared = Vec::new();
+    let mut derives_component = false;
+    for line in source.lines().map(str::trim) {
+        if line.starts_with("#[derive(") {
+            derives_component = line.contains("Component");
+        } else if let Some(rest) = line.strip_prefix("pub struct ") {
+            if derives_component {
+                let name = rest.split(|c: char| !c.is_alphanumeric()).next().unwrap();
+                declared.push(name.to_string());
+            }
+            derives_component = false;
+        }
+    }
+    declared
+}
+
+#[test]
+fn every_declared_component_is_in_the_registry() {
+    let declared = declared_components();
+    assert!(declared.len() > 30);
+    for name in declared {
+        assert!(
+            COMPONENT_NAMES.contains(&name.as_str()),
+            "{} is missing from with_all_components!",
+            name
+        );
+    }
+}
+
+/// Gives one marked entity (or two, for the ones that point at others) every component.
+fn add_one_of_everything(ecs: &mut World) {
+    let player = *ecs.fetch::<Entity>();
+    let thing = ecs
+        .create_entity()
+        .with(Position { x: 3, y: 4 })
+        .with(OtherLevelPosition {
+            x: 5,
+            y: 6,
+            depth: 2,
+        })
+        .with(Renderable {
+            glyph: rltk::to_cp437('x'),
+            fg: rltk::RGB::named(rltk::RED),
+            bg: rltk::RGB::named(rltk::BLACK),
+            render_order: 1,
+        })
+        .with(ParticleLifetime { lifetime_ms: 150.5 })
+        .with(Viewshed {
+            visible_tiles: vec![Point::new(3, 4)],
+            range: 6,
+            dirty: false,
+        })
+        .with(Monster {})
+        .with(Name {
+            name: "Everything".to_string(),
+        })
+        .with(BlocksTile {})
+        .with(CombatStats { max_hp: 12, hp: 7 })
+        .with(Attributes {
+            might: 12,
+            agility: 9,
+            toughness: 11,
+            intellect: 8,
+        })
+        .with(Skills {
+            melee: 3,
+            defense: 1,
+            magic: 2,
+            stealth: 4,
+        })
+        .with(Resistant {
+            damage_types: vec![DamageType::Blunt],
+        })
+        .with(Immune {
+            damage_types: vec![DamageType::Poison, DamageType::Cold],
+        })
+        .with(Vulnerable {
+            damage_types: vec![DamageType::Fire],
+        })
+        .with(SufferDamage {
+            amount: vec![
+                Damage {
+                    amount: 2,
+                    source: Some(player),
+                    damage_type: Some(DamageType::Cold),
+                },
+                Damage {
+                    amount: 3,
+                    source: None,
+                    damage_type: None,
+                },
+            ],
+        })
+        .with(Item {})
+        .with(Consumable {})
+        .with(Ranged { range: 6 })
+        .with(InflictsDamage {
+            damage: 8,
+            damage_type: DamageType::Fire,
+        })
+        .with(AreaOfEffect { radius: 3 })
+        .with(InflictsStatus {
+            effect: StatusEffect {
+                kind: StatusKind::Confusion,
+                turns: 4,
+                potency: 0,
+            },
+        })
+        .with(StatusEffects {
+            effects: vec![
+                StatusEffect {
+                    kind: StatusKind::Poison,
+                    turns: 3,
+                    potency: 2,
+                },
+                StatusEffect {
+                    kind: StatusKind::Haste,
+                    turns: 7,
+                    potency: 0,
+                },
+            ],
+        })
+        .with(ProvidesHealing { heal_amount: 8 })
+        .with(ProvidesFood {})
+        .with(RevealsMap {})
+        .with(MeleeWeapon {
+            damage: rltk::DiceType::new(1, 6, 1),
+            damage_type: DamageType::Piercing,
+            hit_bonus: 1,
+        })
+        .with(DefenseBonus { defense: 1 })
+        .with(Equippable {
+            slot: EquipmentSlot::Shield,
+        })
+        .with(HungerClock {
+            state: HungerState::Hungry,
+            duration: 42,
+        })
+        .with(Hidden {})
+        .with(EntryTrigger {})
+        .with(EntityMoved {})
+        .with(SingleActivation {})
+        .with(Energy {
+            speed: 150,
+            energy: -20,
+        })
+        .with(MyTurn {})
+        .with(Experience { level: 3, xp: 12 })
+        .with(ExperienceValue { xp: 40 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+    ecs.create_entity()
+        .with(InBackpack { owner: player })
+        .with(Equipped {
+            owner: player,
+            slot: EquipmentSlot::Melee,
+        })
+        .with(WantsToMelee { target: thing })
+        .with(WantsToPickupItem {
+            collected_by: player,
+            item: thing,
+        })
+        .with(WantsToUseItem {
+            item: thing,
+            target: Some(Point::new(1, 2)),
+        })
+        .with(WantsToDropItem { item: thing })
+        .with(WantsToRemoveItem { item: thing })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+/// A save's storages keyed by entity marker, without the `SerializationHelper` entity, which
+/// is created fresh for every save. Returns the helper's contents alongside.
+fn comparable(save: &str) -> (Value, Value) {
+    let save: Value = serde_json::from_str(save).unwrap();
+    let storages = save["storages"].as_object().unwrap();
+    let helper = storages["SerializationHelper"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|entry| !entry["components"][0].is_null())
+        .unwrap()
+        .clone();
+    let by_marker = storages
+        .iter()
+        .map(|(name, entries)| {
+            let entries: serde_json::Map<String, Value> = entries
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter(|entry| entry["marker"] != helper["marker"])
+                .map(|entry| (entry["marker"].to_string(), entry["components"].clone()))
+                .collect();
+            (name.clone(), Value::Object(entries))
+        })
+        .collect::<serde_json::Map<_, _>>();
+    (Value::Object(by_marker), helper["components"].clone())
+}
+
+#[test]
+fn every_component_survives_a_save_and_load() {
+    let mut original = HeadlessRunner::new(12);
+    add_one_of_everything(&mut original.state.ecs);
+    let first = save_to_string(&mut original.state.ecs).unwrap();
+
+    let saved: Value = serde_json::from_str(&first).unwrap();
+    for name in COMPONENT_NAMES {
+        let present = saved["storages"][*name].as_array().is_some_and(|entries| {
+            entries
+                .iter()
+                .any(|entry| !entry["components"][0].is_null())
+        });
+        assert!(present, "no {} was saved", name);
+    }
+
+    let mut restored = HeadlessRunner::new(13);
+    load_from_str(&mut restored.state.ecs, &first).unwrap();
+    let second = save_to_string(&mut restored.state.ecs).unwrap();
+
+    let (first_storages, first_helper) = comparable(&first);
+    let (second_storages, second_helper) = comparable(&second);
+    assert_eq!(first_helper, second_helper);
+    for name in COMPONENT_NAMES {
+        assert_eq!(
+            first_storages[*name], second_storages[*name],
+            "{} changed in the round trip",
+            name
+        );
+    }
+}
+
+#[test]
+fn slots_are_written_in_the_chosen_format_without_leftovers() {
+    let mut slots = temp_slots("slot-format");
+    let mut runner = HeadlessRunner::new(5);
+    for format in &[SaveFormat::Json, SaveFormat::Binary] {
+        slots.format = *format;
+        runner.state.ecs.insert(slots.clone());
+        save_game(&mut runner.state.ecs, 0, SaveKind::Manual).unwrap();
+        let written = std::fs::read(slots.path(0)).unwrap();
+        assert_eq!(written.first() == Some(&b'{'), *format == SaveFormat::Json);
+    }
+    let dir = slots.path(0).parent().unwrap().to_path_buf();
+    let files: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+    assert_eq!(files.len(), 1);
+    assert!(matches!(slots.list()[0], SlotStatus::Saved(Some(_))));
+
+    let mut restored = HeadlessRunner::new(6);
+    restored.state.ecs.insert(slots.clone());
+    load_game(&mut restored.state.ecs, 0).unwrap();
+    assert_eq!(player_position(&restored), player_position(&runner));
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+/// A runner that autosaves into its own temporary slots and can't be killed by accident.
+fn autosaving_runner(name: &str) -> (HeadlessRunner, SaveSlots) {
+    let mut slots = temp_slots(name);
+    slots.autosave = true;
+    let mut runner = HeadlessRunner::new(21);
+    runner.state.ecs.insert(slots.clone());
+    let player = runner.player();
+    {
+        let mut stats = runner.state.ecs.write_storage::<CombatStats>();
+        let stats = stats.get_mut(player).unwrap();
+        stats.max_hp = 100_000;
+        stats.hp = 100_000;
+    }
+    (runner, slots)
+}
+
+fn descend(runner: &mut HeadlessRunner) {
+    let stairs = {
+        let map = runner.state.ecs.fetch::<Map>();
+        let idx = map
+            .tiles
+            .iter()
+            .position(|t| *t == TileType::DownStairs)
+            .unwrap();
+        Point::new(idx as i32 % map.width, idx as i32 / map.width)
+    };
+    let player = runner.player();
+    {
+        let mut positions = runner.state.ecs.write_storage::<Position>();
+        let pos = positions.get_mut(player).unwrap();
+        pos.x = stairs.x;
+        pos.y = stairs.y;
+    }
+    *runner.state.ecs.write_resource::<Point>() = stairs;
+    runner.submit(Command::Descend);
+}
+
+fn cleanup(slots: &SaveSlots) {
+    let _ = std::fs::remove_dir_all(slots.path(0).parent().unwrap());
+}
+
+#[test]
+fn the_game_autosaves_on_new_levels_and_every_so_many_turns() {
+    let (mut runner, slots) = autosaving_runner("autosave");
+    assert!(!slots.any_used());
+
+    descend(&mut runner);
+    let (slot, summary) = slots.find_autosave().unwrap();
+    assert_eq!(slot, 0);
+    assert_eq!(summary.depth, 2);
+
+    while runner.state.ecs.fetch::<GameClock>().turn < AUTOSAVE_INTERVAL {
+        runner.submit(Command::Wait);
+    }
+    let (slot, summary) = slots.find_autosave().unwrap();
+    assert_eq!(slot, 0);
+    assert_eq!(summary.turn, AUTOSAVE_INTERVAL);
+    assert_eq!(
+        slots
+            .list()
+            .iter()
+            .filter(|s| **s != SlotStatus::Empty)
+            .count(),
+        1
+    );
+    cleanup(&slots);
+}
+
+#[test]
+fn a_character_only_ever_has_one_save() {
+    let (mut runner, slots) = autosaving_runner("one-save");
+    descend(&mut runner);
+    assert!(slots.is_used(0));
+
+    save_game(&mut runner.state.ecs, 3, SaveKind::Manual).unwrap();
+    assert!(!slots.is_used(0));
+    assert!(slots.is_used(3));
+    assert_eq!(slots.find_autosave(), None);
+
+    let mut restored = HeadlessRunner::new(22);
+    restored.state.ecs.insert(slots.clone());
+    load_game(&mut restored.state.ecs, 3).unwrap();
+    assert!(!slots.any_used());
+    cleanup(&slots);
+}
+
+#[test]
+fn dying_deletes_the_save() {
+    let (mut runner, slots) = autosaving_runner("death");
+    descend(&mut runner);
+    assert!(slots.is_used(0));
+
+    let player = runner.player();
+    runner
+        .state
+        .ecs
+        .write_storage::<CombatStats>()
+        .get_mut(player)
+        .unwrap()
+        .hp = -100;
+    assert!(runner.submit(Command::Wait) == RunState::GameOver);
+    assert!(!slots.any_used());
+    cleanup(&slots);
+}